@@ -30,6 +30,7 @@ pub fn viewport_builder<E>(
         window_settings
             .clamp_size_to_sane_values(largest_monitor_point_size(egui_zoom_factor, event_loop));
         window_settings.clamp_position_to_monitors(egui_zoom_factor, event_loop);
+        window_settings.clamp_to_monitors(event_loop.available_monitors());
 
         viewport_builder = window_settings.initialize_viewport_builder(viewport_builder);
         window_settings.inner_size_points()
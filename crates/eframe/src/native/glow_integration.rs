@@ -749,10 +749,7 @@ impl GlowWinitRunning {
             return EventResult::Exit;
         }
 
-        let mut event_response = egui_winit::EventResponse {
-            consumed: false,
-            repaint: false,
-        };
+        let mut event_response = egui_winit::EventResponse::default();
         if let Some(viewport_id) = viewport_id {
             if let Some(viewport) = glutin.viewports.get_mut(&viewport_id) {
                 if let (Some(window), Some(egui_winit)) =
@@ -1064,13 +1061,16 @@ impl GlutinWindowContext {
             // create window/surface/make context current once and just use them forever.
 
             viewport.egui_winit.get_or_insert_with(|| {
-                egui_winit::State::new(
+                let mut egui_winit = egui_winit::State::new(
                     self.egui_ctx.clone(),
                     viewport_id,
                     event_loop,
                     Some(window.scale_factor() as f32),
                     self.max_texture_side,
-                )
+                );
+                // Let mice without a tilt wheel scroll horizontally:
+                egui_winit.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
+                egui_winit
             });
 
             viewport.gl_surface = Some(gl_surface);
@@ -1178,14 +1178,16 @@ impl GlutinWindowContext {
 
             if let Some(window) = &viewport.window {
                 let is_viewport_focused = self.focused_viewport == Some(viewport_id);
-                egui_winit::process_viewport_commands(
+                for (command, err) in egui_winit::process_viewport_commands(
                     egui_ctx,
                     &mut viewport.info,
                     commands,
                     window,
                     is_viewport_focused,
                     &mut viewport.screenshot_requested,
-                );
+                ) {
+                    log::warn!("{viewport_id:?}: {command:?}: {err}");
+                }
             }
         }
 
@@ -1254,14 +1256,16 @@ fn initialize_or_update_viewport<'vp>(
                 viewport.egui_winit = None;
             } else if let Some(window) = &viewport.window {
                 let is_viewport_focused = focused_viewport == Some(ids.this);
-                egui_winit::process_viewport_commands(
+                for (command, err) in egui_winit::process_viewport_commands(
                     egu_ctx,
                     &mut viewport.info,
                     delta_commands,
                     window,
                     is_viewport_focused,
                     &mut viewport.screenshot_requested,
-                );
+                ) {
+                    log::warn!("{:?}: {command:?}: {err}", ids.this);
+                }
             }
 
             entry.into_mut()
@@ -203,7 +203,6 @@ impl WgpuWinitApp {
             });
         }
 
-        #[allow(unused_mut)] // used for accesskit
         let mut egui_winit = egui_winit::State::new(
             egui_ctx.clone(),
             ViewportId::ROOT,
@@ -211,6 +210,8 @@ impl WgpuWinitApp {
             Some(window.scale_factor() as f32),
             painter.max_texture_side(),
         );
+        // Let mice without a tilt wheel scroll horizontally:
+        egui_winit.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
 
         #[cfg(feature = "accesskit")]
         {
@@ -795,13 +796,16 @@ impl Viewport {
                     log::error!("on set_window: viewport_id {viewport_id:?} {err}");
                 }
 
-                self.egui_winit = Some(egui_winit::State::new(
+                let mut egui_winit = egui_winit::State::new(
                     egui_ctx.clone(),
                     viewport_id,
                     event_loop,
                     Some(window.scale_factor() as f32),
                     painter.max_texture_side(),
-                ));
+                );
+                // Let mice without a tilt wheel scroll horizontally:
+                egui_winit.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
+                self.egui_winit = Some(egui_winit);
 
                 self.info.minimized = window.is_minimized();
                 self.info.maximized = Some(window.is_maximized());
@@ -979,14 +983,16 @@ fn handle_viewport_output(
 
         if let Some(window) = viewport.window.as_ref() {
             let is_viewport_focused = focused_viewport == Some(viewport_id);
-            egui_winit::process_viewport_commands(
+            for (command, err) in egui_winit::process_viewport_commands(
                 egui_ctx,
                 &mut viewport.info,
                 commands,
                 window,
                 is_viewport_focused,
                 &mut viewport.screenshot_requested,
-            );
+            ) {
+                log::warn!("{viewport_id:?}: {command:?}: {err}");
+            }
         }
     }
 }
@@ -1045,14 +1051,16 @@ fn initialize_or_update_viewport<'vp>(
                 viewport.egui_winit = None;
             } else if let Some(window) = &viewport.window {
                 let is_viewport_focused = focused_viewport == Some(ids.this);
-                egui_winit::process_viewport_commands(
+                for (command, err) in egui_winit::process_viewport_commands(
                     egui_ctx,
                     &mut viewport.info,
                     delta_commands,
                     window,
                     is_viewport_focused,
                     &mut viewport.screenshot_requested,
-                );
+                ) {
+                    log::warn!("{:?}: {command:?}: {err}", ids.this);
+                }
             }
 
             entry.into_mut()
@@ -1,10 +1,295 @@
+use std::{sync::Arc, time::Duration};
+
+use egui::mutex::Mutex;
+
 use raw_window_handle::HasRawDisplayHandle;
 
+/// Something that can hold text copied from egui, and provide text to paste into it.
+///
+/// [`Clipboard`] is the default, OS-backed implementation.
+/// Implement this to e.g. use an in-process buffer in a sandbox, or a remote clipboard,
+/// and pass it to [`crate::State::new_with_clipboard`].
+pub trait ClipboardProvider: Send + Sync {
+    /// The text to paste, if any.
+    fn get(&mut self) -> Option<String>;
+
+    /// Copy this text.
+    fn set(&mut self, text: String);
+
+    /// The image to paste, if any.
+    ///
+    /// The default implementation has no images.
+    fn get_image(&mut self) -> Option<egui::ColorImage> {
+        None
+    }
+
+    /// Copy this image.
+    ///
+    /// The default implementation ignores images.
+    fn set_image(&mut self, _image: &egui::ColorImage) {}
+
+    /// The text of the primary selection (X11 and Wayland), e.g. for middle-click paste.
+    ///
+    /// The default implementation has no primary selection.
+    fn get_primary(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Set the primary selection (X11 and Wayland).
+    ///
+    /// The default implementation has no primary selection.
+    fn set_primary(&mut self, _text: String) {}
+
+    /// Called by [`crate::State::set_clipboard_display`].
+    ///
+    /// The default implementation does nothing.
+    fn init_display(&mut self, _display_target: &dyn HasRawDisplayHandle) {}
+
+    /// Called by [`crate::State::set_clipboard_timeout`].
+    ///
+    /// The default implementation does nothing.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) {}
+}
+
 /// Handles interfacing with the OS clipboard.
 ///
 /// If the "clipboard" feature is off, or we cannot connect to the OS clipboard,
 /// then a fallback clipboard that just works within the same app is used instead.
 pub struct Clipboard {
+    /// The OS clipboard(s), shared with the [`Worker`] when a [`Self::set_timeout`] is used.
+    os: Arc<Mutex<OsClipboard>>,
+
+    /// Whether [`Self::os`] has any backend, cached so we never wait for its lock to find out.
+    os_available: bool,
+
+    /// Runs the OS clipboard calls while there is a timeout. Spawned on first use.
+    worker: Option<Worker>,
+
+    /// Fallback manual clipboard.
+    clipboard: String,
+
+    /// How long to wait for the OS clipboard before giving up.
+    ///
+    /// `None` means wait forever.
+    timeout: Option<Duration>,
+
+    /// Created with [`Self::deferred`], and still waiting for [`Self::init_display`].
+    deferred: bool,
+}
+
+impl Clipboard {
+    /// Construct a new instance
+    pub fn new(_display_target: &dyn HasRawDisplayHandle) -> Self {
+        let os = OsClipboard::new(_display_target);
+        Self {
+            os_available: os.is_available(),
+            os: Arc::new(Mutex::new(os)),
+            worker: None,
+            clipboard: Default::default(),
+            timeout: None,
+            deferred: false,
+        }
+    }
+
+    /// A clipboard that does nothing until [`Self::init_display`] is called.
+    ///
+    /// Use this when the display handle isn't available yet.
+    pub fn deferred() -> Self {
+        Self {
+            os: Default::default(),
+            os_available: false,
+            worker: None,
+            clipboard: Default::default(),
+            timeout: None,
+            deferred: true,
+        }
+    }
+
+    /// Connect to the OS clipboard of the given display.
+    ///
+    /// Replaces any previous connection.
+    pub fn init_display(&mut self, display_target: &dyn HasRawDisplayHandle) {
+        self.connect(OsClipboard::new(display_target));
+    }
+
+    fn connect(&mut self, os: OsClipboard) {
+        self.os_available = os.is_available();
+        self.os = Arc::new(Mutex::new(os));
+        self.deferred = false;
+    }
+
+    /// Give up on reading or writing the OS clipboard after this long.
+    ///
+    /// This protects against the UI thread hanging on slow clipboards,
+    /// e.g. over X11 forwarding or remote desktop sessions.
+    /// While a timeout is set, the OS clipboard is accessed from a background thread.
+    /// If an earlier access is still stuck there, later ones time out too, instead of waiting for it.
+    ///
+    /// `None` (the default) means to wait forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// See [`Self::set_timeout`].
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Run `f` on the OS clipboard, giving up after [`Self::timeout`].
+    ///
+    /// Returns `Err` with the timeout if we gave up.
+    fn with_os<R: Send + 'static>(
+        &mut self,
+        f: impl FnOnce(&mut OsClipboard) -> R + Send + 'static,
+    ) -> Result<R, Duration> {
+        let Some(timeout) = self.timeout else {
+            return Ok(f(&mut self.os.lock()));
+        };
+
+        if self.worker.is_none() {
+            self.worker = Worker::spawn();
+        }
+        let Some(worker) = &self.worker else {
+            return Err(timeout);
+        };
+
+        let os = self.os.clone();
+        worker
+            .run(timeout, move || f(&mut os.lock()))
+            .ok_or(timeout)
+    }
+
+    pub fn get(&mut self) -> Option<String> {
+        if self.deferred {
+            log::warn!("Clipboard not initialized yet; ignoring paste");
+            return None;
+        }
+
+        if !self.os_available {
+            return Some(self.clipboard.clone());
+        }
+
+        self.with_os(|os| os.get()).unwrap_or_else(|timeout| {
+            log::warn!("Timed out after {timeout:?} when reading the clipboard");
+            None
+        })
+    }
+
+    pub fn set(&mut self, text: String) {
+        if self.deferred {
+            log::warn!("Clipboard not initialized yet; ignoring copy");
+            return;
+        }
+
+        if !self.os_available {
+            self.clipboard = text;
+            return;
+        }
+
+        if let Err(timeout) = self.with_os(|os| os.set(text)) {
+            log::warn!("Timed out after {timeout:?} when writing to the clipboard");
+        }
+    }
+
+    /// Read an image from the OS clipboard, if it holds one.
+    ///
+    /// Always `None` when there is no OS clipboard.
+    pub fn get_image(&mut self) -> Option<egui::ColorImage> {
+        if self.deferred || !self.os_available {
+            return None;
+        }
+
+        self.with_os(|os| os.get_image()).unwrap_or_else(|timeout| {
+            log::warn!("Timed out after {timeout:?} when reading an image from the clipboard");
+            None
+        })
+    }
+
+    /// Put an image on the OS clipboard.
+    ///
+    /// Does nothing when there is no OS clipboard.
+    pub fn set_image(&mut self, image: &egui::ColorImage) {
+        if self.deferred {
+            log::warn!("Clipboard not initialized yet; ignoring image copy");
+            return;
+        }
+
+        if !self.os_available {
+            return;
+        }
+
+        let image = image.clone();
+        if let Err(timeout) = self.with_os(move |os| os.set_image(&image)) {
+            log::warn!("Timed out after {timeout:?} when writing an image to the clipboard");
+        }
+    }
+
+    /// Read the primary selection, which is what middle-click pastes on X11 and Wayland.
+    ///
+    /// Always `None` on other platforms, or when there is no OS clipboard.
+    pub fn get_primary(&mut self) -> Option<String> {
+        if self.deferred || !self.os_available {
+            return None;
+        }
+
+        self.with_os(|os| os.get_primary())
+            .unwrap_or_else(|timeout| {
+                log::warn!("Timed out after {timeout:?} when reading the primary selection");
+                None
+            })
+    }
+
+    /// Set the primary selection on X11 and Wayland.
+    ///
+    /// Does nothing on other platforms, or when there is no OS clipboard.
+    pub fn set_primary(&mut self, text: String) {
+        if self.deferred || !self.os_available {
+            return;
+        }
+
+        if let Err(timeout) = self.with_os(|os| os.set_primary(text)) {
+            log::warn!("Timed out after {timeout:?} when writing the primary selection");
+        }
+    }
+}
+
+impl ClipboardProvider for Clipboard {
+    fn get(&mut self) -> Option<String> {
+        Self::get(self)
+    }
+
+    fn set(&mut self, text: String) {
+        Self::set(self, text);
+    }
+
+    fn get_image(&mut self) -> Option<egui::ColorImage> {
+        Self::get_image(self)
+    }
+
+    fn set_image(&mut self, image: &egui::ColorImage) {
+        Self::set_image(self, image);
+    }
+
+    fn get_primary(&mut self) -> Option<String> {
+        Self::get_primary(self)
+    }
+
+    fn set_primary(&mut self, text: String) {
+        Self::set_primary(self, text);
+    }
+
+    fn init_display(&mut self, display_target: &dyn HasRawDisplayHandle) {
+        Self::init_display(self, display_target);
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        Self::set_timeout(self, timeout);
+    }
+}
+
+/// The connections to the OS clipboard, if any.
+#[derive(Default)]
+struct OsClipboard {
     #[cfg(all(feature = "arboard", not(target_os = "android")))]
     arboard: Option<arboard::Clipboard>,
 
@@ -19,14 +304,10 @@ pub struct Clipboard {
         feature = "smithay-clipboard"
     ))]
     smithay: Option<smithay_clipboard::Clipboard>,
-
-    /// Fallback manual clipboard.
-    clipboard: String,
 }
 
-impl Clipboard {
-    /// Construct a new instance
-    pub fn new(_display_target: &dyn HasRawDisplayHandle) -> Self {
+impl OsClipboard {
+    fn new(_display_target: &dyn HasRawDisplayHandle) -> Self {
         Self {
             #[cfg(all(feature = "arboard", not(target_os = "android")))]
             arboard: init_arboard(),
@@ -42,12 +323,33 @@ impl Clipboard {
                 feature = "smithay-clipboard"
             ))]
             smithay: init_smithay_clipboard(_display_target),
+        }
+    }
 
-            clipboard: Default::default(),
+    fn is_available(&self) -> bool {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if self.smithay.is_some() {
+            return true;
         }
+
+        #[cfg(all(feature = "arboard", not(target_os = "android")))]
+        if self.arboard.is_some() {
+            return true;
+        }
+
+        false
     }
 
-    pub fn get(&mut self) -> Option<String> {
+    fn get(&mut self) -> Option<String> {
         #[cfg(all(
             any(
                 target_os = "linux",
@@ -72,6 +374,7 @@ impl Clipboard {
         if let Some(clipboard) = &mut self.arboard {
             return match clipboard.get_text() {
                 Ok(text) => Some(text),
+                Err(arboard::Error::ContentNotAvailable) => None,
                 Err(err) => {
                     log::error!("arboard paste error: {err}");
                     None
@@ -79,10 +382,10 @@ impl Clipboard {
             };
         }
 
-        Some(self.clipboard.clone())
+        None
     }
 
-    pub fn set(&mut self, text: String) {
+    fn set(&mut self, _text: String) {
         #[cfg(all(
             any(
                 target_os = "linux",
@@ -94,19 +397,187 @@ impl Clipboard {
             feature = "smithay-clipboard"
         ))]
         if let Some(clipboard) = &mut self.smithay {
-            clipboard.store(text);
+            clipboard.store(_text);
             return;
         }
 
         #[cfg(all(feature = "arboard", not(target_os = "android")))]
         if let Some(clipboard) = &mut self.arboard {
-            if let Err(err) = clipboard.set_text(text) {
+            if let Err(err) = clipboard.set_text(_text) {
                 log::error!("arboard copy/cut error: {err}");
             }
+        }
+    }
+
+    fn get_primary(&mut self) -> Option<String> {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if let Some(clipboard) = &mut self.smithay {
+            return match clipboard.load_primary() {
+                Ok(text) => Some(text),
+                Err(err) => {
+                    log::error!("smithay primary selection paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "arboard"
+        ))]
+        if let Some(clipboard) = &mut self.arboard {
+            use arboard::{GetExtLinux as _, LinuxClipboardKind};
+            return match clipboard
+                .get()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text()
+            {
+                Ok(text) => Some(text),
+                Err(arboard::Error::ContentNotAvailable) => None,
+                Err(err) => {
+                    log::error!("arboard primary selection paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        None
+    }
+
+    fn set_primary(&mut self, _text: String) {
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "smithay-clipboard"
+        ))]
+        if let Some(clipboard) = &mut self.smithay {
+            clipboard.store_primary(_text);
             return;
         }
 
-        self.clipboard = text;
+        #[cfg(all(
+            any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ),
+            feature = "arboard"
+        ))]
+        if let Some(clipboard) = &mut self.arboard {
+            use arboard::{LinuxClipboardKind, SetExtLinux as _};
+            if let Err(err) = clipboard
+                .set()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text(_text)
+            {
+                log::error!("arboard primary selection copy error: {err}");
+            }
+        }
+    }
+
+    fn get_image(&mut self) -> Option<egui::ColorImage> {
+        #[cfg(all(feature = "arboard", not(target_os = "android")))]
+        if let Some(clipboard) = &mut self.arboard {
+            return match clipboard.get_image() {
+                Ok(image) => Some(egui::ColorImage::from_rgba_unmultiplied(
+                    [image.width, image.height],
+                    &image.bytes,
+                )),
+                Err(arboard::Error::ContentNotAvailable) => None,
+                Err(err) => {
+                    log::error!("arboard image paste error: {err}");
+                    None
+                }
+            };
+        }
+
+        None
+    }
+
+    fn set_image(&mut self, _image: &egui::ColorImage) {
+        #[cfg(all(feature = "arboard", not(target_os = "android")))]
+        if let Some(clipboard) = &mut self.arboard {
+            let [width, height] = _image.size;
+            let bytes = _image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_srgba_unmultiplied())
+                .collect::<Vec<u8>>();
+            let image = arboard::ImageData {
+                width,
+                height,
+                bytes: bytes.into(),
+            };
+            if let Err(err) = clipboard.set_image(image) {
+                log::error!("arboard image copy error: {err}");
+            }
+        }
+    }
+}
+
+/// A long-lived thread that runs clipboard calls one at a time,
+/// so that the UI thread can stop waiting for a slow one.
+struct Worker {
+    jobs: std::sync::mpsc::Sender<Box<dyn FnOnce() + Send>>,
+}
+
+impl Worker {
+    fn spawn() -> Option<Self> {
+        let (jobs, rx) = std::sync::mpsc::channel::<Box<dyn FnOnce() + Send>>();
+        let spawned = std::thread::Builder::new()
+            .name("egui-winit clipboard".to_owned())
+            .spawn(move || {
+                for job in rx {
+                    job();
+                }
+            });
+        match spawned {
+            Ok(_) => Some(Self { jobs }),
+            Err(err) => {
+                log::warn!("Failed to spawn clipboard thread: {err}");
+                None
+            }
+        }
+    }
+
+    /// Run `f` on the worker thread, giving up on it after `timeout`.
+    ///
+    /// If an earlier call is still running, `f` is queued behind it,
+    /// and we only wait `timeout` for both.
+    fn run<R: Send + 'static>(
+        &self,
+        timeout: Duration,
+        f: impl FnOnce() -> R + Send + 'static,
+    ) -> Option<R> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.jobs
+            .send(Box::new(move || {
+                tx.send(f()).ok();
+            }))
+            .ok()?;
+        rx.recv_timeout(timeout).ok()
     }
 }
 
@@ -154,3 +625,59 @@ fn init_smithay_clipboard(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, OsClipboard, Worker};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_slow_clipboard_times_out() {
+        let slow_backend = || {
+            std::thread::sleep(Duration::from_secs(5));
+            Some("too late".to_owned())
+        };
+
+        let worker = Worker::spawn().unwrap();
+        let start = Instant::now();
+        let text = worker.run(Duration::from_millis(10), slow_backend);
+        assert_eq!(text, None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_fast_clipboard_within_timeout() {
+        let worker = Worker::spawn().unwrap();
+        let text = worker.run(Duration::from_secs(5), || Some("hello".to_owned()));
+        assert_eq!(text, Some(Some("hello".to_owned())));
+    }
+
+    #[test]
+    fn test_deferred_clipboard() {
+        let mut clipboard = Clipboard::deferred();
+        clipboard.set("ignored".to_owned());
+        assert_eq!(clipboard.get(), None);
+
+        // Without an OS clipboard, so that we don't touch the real one:
+        clipboard.connect(OsClipboard::default());
+        clipboard.set("copied".to_owned());
+        assert_eq!(clipboard.get().as_deref(), Some("copied"));
+    }
+
+    #[test]
+    fn test_stuck_clipboard_does_not_block_later_calls() {
+        let mut clipboard = Clipboard::deferred();
+        clipboard.deferred = false;
+        clipboard.os_available = true;
+        clipboard.set_timeout(Some(Duration::from_millis(10)));
+
+        // An earlier call that is stuck in the OS clipboard:
+        let stuck = clipboard.with_os(|_os| std::thread::sleep(Duration::from_secs(5)));
+        assert_eq!(stuck, Err(Duration::from_millis(10)));
+
+        let start = Instant::now();
+        assert_eq!(clipboard.get(), None);
+        clipboard.set("hello".to_owned());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -20,7 +20,9 @@ pub use winit;
 pub mod clipboard;
 mod window_settings;
 
-pub use window_settings::WindowSettings;
+pub use window_settings::{
+    available_monitors, monitors_changed_since, MonitorInfo, WindowSettings,
+};
 
 use raw_window_handle::HasRawDisplayHandle;
 
@@ -57,10 +59,399 @@ pub struct EventResponse {
     /// pass on the events to your game when [`Self::consumed`] is `false.
     ///
     /// Note that egui uses `tab` to move focus between elements, so this will always be `true` for tabs.
+    ///
+    /// Use [`Self::consumed_pointer`] and [`Self::consumed_keyboard`] to tell the two apart.
     pub consumed: bool,
 
     /// Do we need an egui refresh because of this event?
     pub repaint: bool,
+
+    /// Why egui consumed this event, if it did.
+    ///
+    /// Useful for debugging why egui is eating input meant for e.g. a game.
+    pub consume_reason: Option<ConsumeReason>,
+
+    /// `Some(focused)` if this was a focus change of the window, else `None`.
+    ///
+    /// Use this to e.g. close popups and context menus when the window loses focus.
+    pub focus_changed: Option<bool>,
+}
+
+impl EventResponse {
+    /// Did egui consume this event because it is using the pointer,
+    /// or because the pointer is over egui?
+    ///
+    /// Pointer and keyboard consumption are independent, so e.g. a game can keep
+    /// using the mouse for its camera while an egui text field has keyboard focus.
+    #[inline]
+    pub fn consumed_pointer(&self) -> bool {
+        self.consume_reason
+            .is_some_and(|reason| reason.is_pointer())
+    }
+
+    /// Did egui consume this event because it is using the keyboard, e.g. for a focused text field?
+    ///
+    /// See also [`Self::consumed_pointer`].
+    #[inline]
+    pub fn consumed_keyboard(&self) -> bool {
+        self.consume_reason
+            .is_some_and(|reason| !reason.is_pointer())
+    }
+}
+
+/// Why egui consumed an event.
+///
+/// See [`EventResponse::consume_reason`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsumeReason {
+    /// The pointer is over an egui area, e.g. a window or a panel.
+    PointerOverArea,
+
+    /// egui is using the pointer, e.g. for dragging a slider.
+    PointerInUse,
+
+    /// An egui widget has keyboard focus, e.g. a text field.
+    KeyboardFocus,
+
+    /// egui uses Tab to move focus between widgets, so Tab is always consumed.
+    TabTraversal,
+
+    /// The callback given to [`State::set_keyboard_capture`] consumed it.
+    KeyboardCapture,
+}
+
+impl ConsumeReason {
+    /// Was the event consumed because of the pointer (as opposed to the keyboard)?
+    pub fn is_pointer(self) -> bool {
+        match self {
+            Self::PointerOverArea | Self::PointerInUse => true,
+            Self::KeyboardFocus | Self::TabTraversal | Self::KeyboardCapture => false,
+        }
+    }
+}
+
+/// What [`State::handle_platform_output`] did.
+///
+/// Useful for logging, and for asserting on side effects in tests.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlatformOutputActions {
+    /// The mouse cursor icon was changed.
+    pub cursor_changed: bool,
+
+    /// This url was opened.
+    pub opened_url: Option<String>,
+
+    /// Text was copied to the clipboard.
+    pub copied: bool,
+
+    /// The IME position was updated.
+    pub ime_moved: bool,
+}
+
+// ----------------------------------------------------------------------------
+
+/// Which parts of the [`egui::PlatformOutput`] [`State::handle_platform_output`] should apply.
+///
+/// Turn these off if you handle some of them yourself, e.g. when embedding egui in another app.
+/// Everything is applied by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformOutputOptions {
+    /// Set the cursor icon of the window.
+    pub apply_cursor: bool,
+
+    /// Copy text to the clipboard.
+    pub apply_clipboard: bool,
+
+    /// Open URLs in the browser.
+    pub apply_open_url: bool,
+
+    /// Allow IME and move the IME candidate window to the text cursor.
+    pub apply_ime: bool,
+}
+
+impl Default for PlatformOutputOptions {
+    fn default() -> Self {
+        Self {
+            apply_cursor: true,
+            apply_clipboard: true,
+            apply_open_url: true,
+            apply_ime: true,
+        }
+    }
+}
+
+/// Which keyboard conventions to follow when translating modifier keys for egui.
+///
+/// This decides which key acts as [`egui::Modifiers::command`] (used for shortcuts like copy/paste)
+/// and whether [`egui::Modifiers::mac_cmd`] is set (used for line-wise text navigation).
+///
+/// See [`State::set_text_nav_style`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextNavStyle {
+    /// Cmd is the command key, Cmd+arrows jump to line start/end, Alt+arrows jump words.
+    Mac,
+
+    /// Ctrl is the command key, Ctrl+arrows jump words, Home/End jump to line start/end.
+    Windows,
+
+    /// Super (the logo key) is the command key,
+    /// leaving Ctrl for text-editing bindings like Ctrl+W and Ctrl+K.
+    /// Alt/Ctrl+arrows jump words.
+    Emacs,
+}
+
+impl Default for TextNavStyle {
+    /// [`Self::Mac`] on Mac, otherwise [`Self::Windows`].
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::Mac
+        } else {
+            Self::Windows
+        }
+    }
+}
+
+/// Which line endings to use for text copied to the clipboard.
+///
+/// See [`State::set_copy_newline_style`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NewlineStyle {
+    /// `\n`
+    Lf,
+
+    /// `\r\n`
+    CrLf,
+
+    /// [`Self::CrLf`] on Windows, otherwise [`Self::Lf`].
+    #[default]
+    Platform,
+}
+
+/// What to do when the user "force clicks" (presses harder) on a Force Touch trackpad.
+///
+/// See [`State::set_force_touch`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ForceClickAction {
+    /// Click the secondary pointer button at the current pointer position.
+    SecondaryClick,
+
+    /// Push this event to egui.
+    Event(egui::Event),
+}
+
+/// A clipboard operation on the focused egui widget.
+///
+/// See [`State::trigger_clipboard_action`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardAction {
+    Copy,
+    Cut,
+    Paste,
+}
+
+/// Where pasted text came from.
+///
+/// See [`State::last_paste_source`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasteSource {
+    /// The regular clipboard, e.g. from ctrl/cmd+V or an Edit menu.
+    Clipboard,
+
+    /// The primary selection (X11 and Wayland), e.g. from a middle-click.
+    ///
+    /// A middle-click only pastes when the pointer is over egui and egui wants keyboard input
+    /// (e.g. a text field has focus), so clicks meant for the app behind egui are left alone.
+    Primary,
+
+    /// The app, using [`State::inject_paste`].
+    Injected,
+}
+
+/// An action triggered from a native (OS) menu, to be forwarded to egui
+/// with [`State::on_menu_action`].
+///
+/// This lets you wire e.g. a macOS menu bar (via `muda` or similar) to egui commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    Copy,
+    Cut,
+    Paste,
+    SelectAll,
+    Undo,
+    Redo,
+
+    /// Press (and release) this keyboard shortcut.
+    Shortcut(egui::KeyboardShortcut),
+}
+
+impl MenuAction {
+    /// The keyboard shortcut egui would normally receive for this action.
+    ///
+    /// `None` for the clipboard actions, which are sent as dedicated events instead.
+    pub fn shortcut(self) -> Option<egui::KeyboardShortcut> {
+        use egui::{Key, KeyboardShortcut};
+
+        let command = command_modifiers();
+        match self {
+            Self::Copy | Self::Cut | Self::Paste => None,
+            Self::SelectAll => Some(KeyboardShortcut::new(command, Key::A)),
+            Self::Undo => Some(KeyboardShortcut::new(command, Key::Z)),
+            Self::Redo => Some(KeyboardShortcut::new(
+                command | egui::Modifiers::SHIFT,
+                Key::Z,
+            )),
+            Self::Shortcut(shortcut) => Some(shortcut),
+        }
+    }
+}
+
+/// The modifiers of the platform's command key (`Cmd` on Mac, `Ctrl` elsewhere).
+///
+/// Like the modifiers egui-winit reports for real key presses, so that egui's
+/// `matches(Modifiers::COMMAND)` checks (e.g. in [`egui::TextEdit`]) accept them.
+fn command_modifiers() -> egui::Modifiers {
+    if cfg!(target_os = "macos") {
+        egui::Modifiers::MAC_CMD | egui::Modifiers::COMMAND
+    } else {
+        egui::Modifiers::COMMAND
+    }
+}
+
+/// When to treat mouse input as touch input.
+///
+/// Simulated touches use a reserved device and touch id,
+/// so they never get mixed up with real touches.
+///
+/// See [`State::set_touch_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TouchMode {
+    /// Simulate touches from the mouse until a real touch event arrives.
+    ///
+    /// This avoids duplicate touches on devices with a real touch screen.
+    Auto,
+
+    /// Always treat mouse input as touches.
+    ///
+    /// Useful for debugging touch support in egui.
+    /// Creates duplicate touches if real touch inputs are coming.
+    ForceTouch,
+
+    /// Never treat mouse input as touches.
+    #[default]
+    ForceMouse,
+}
+
+// ----------------------------------------------------------------------------
+
+/// Input from a source other than winit, e.g. events streamed from a remote client.
+///
+/// This is the subset of [`winit::event::WindowEvent`] that egui cares about,
+/// already in egui units.
+///
+/// See [`State::on_remote_event`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum RemoteEvent {
+    /// The pointer moved to this position, in points.
+    PointerMoved(egui::Pos2),
+
+    /// A pointer button was pressed or released at the current pointer position.
+    PointerButton {
+        button: egui::PointerButton,
+        pressed: bool,
+    },
+
+    /// The pointer left the window.
+    PointerGone,
+
+    /// A key was pressed or released.
+    ///
+    /// Clipboard shortcuts are not detected here:
+    /// send the pasted text as [`Self::Text`] instead.
+    Key { key: egui::Key, pressed: bool },
+
+    /// Text input.
+    Text(String),
+
+    /// Mouse wheel or touchpad scrolling.
+    ///
+    /// `delta` is in lines, points or pages, depending on `unit`.
+    /// A page is the height of the screen.
+    Scroll {
+        unit: egui::MouseWheelUnit,
+        delta: egui::Vec2,
+    },
+
+    /// The modifier keys that are now held down.
+    Modifiers(egui::Modifiers),
+
+    /// The window gained or lost focus.
+    Focused(bool),
+}
+
+// ----------------------------------------------------------------------------
+
+/// How to map [`winit::event::WindowEvent::AxisMotion`] to pointer movement.
+///
+/// This is meant for specialized input devices, such as accessibility joysticks,
+/// that present themselves to winit as pointing axes.
+/// Regular mice and touchpads do not need this.
+///
+/// See [`State::set_axis_pointer`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisPointerConfig {
+    /// The axis that moves the pointer horizontally.
+    pub x_axis: u32,
+
+    /// The axis that moves the pointer vertically.
+    pub y_axis: u32,
+
+    /// How many points the pointer moves per unit of axis motion.
+    pub points_per_unit: f32,
+}
+
+impl Default for AxisPointerConfig {
+    fn default() -> Self {
+        Self {
+            x_axis: 0,
+            y_axis: 1,
+            points_per_unit: 1.0,
+        }
+    }
+}
+
+/// How to accelerate scrolling while a navigation key (arrows, page up/down) is held down.
+///
+/// Each key repeat scrolls by [`Self::points_per_repeat`], which grows the longer the key is held.
+///
+/// See [`State::set_key_repeat_acceleration`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccelCurve {
+    /// How many points to scroll per key repeat right after the key was pressed.
+    pub initial_points: f32,
+
+    /// How many points per key repeat to add for each second the key has been held.
+    pub points_per_second: f32,
+
+    /// The most points to scroll per key repeat.
+    pub max_points: f32,
+}
+
+impl Default for AccelCurve {
+    fn default() -> Self {
+        Self {
+            initial_points: 0.0,
+            points_per_second: 50.0,
+            max_points: 200.0,
+        }
+    }
+}
+
+impl AccelCurve {
+    /// How many points to scroll for a key repeat, when the key has been held for `held`.
+    pub fn points_per_repeat(&self, held: std::time::Duration) -> f32 {
+        (self.initial_points + self.points_per_second * held.as_secs_f32()).min(self.max_points)
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -77,38 +468,336 @@ pub struct State {
     egui_input: egui::RawInput,
     pointer_pos_in_points: Option<egui::Pos2>,
     any_pointer_button_down: bool,
+
+    /// If `true`, ignore presses of other pointer buttons while one is held down.
+    single_pointer_button: bool,
+
+    /// Which egui button each [`winit::event::MouseButton::Other`] maps to,
+    /// before falling back to [`translate_mouse_button`].
+    mouse_button_mapping: egui::ahash::HashMap<u16, egui::PointerButton>,
+
+    /// If `true`, the pointer position is rounded to whole physical pixels.
+    round_pointer_to_pixels: bool,
+
+    /// If `true`, the pointer is kept when it leaves the window during a drag.
+    drag_sticky: bool,
+
+    /// The pointer left the window during a sticky drag, and should be forgotten once it ends.
+    left_during_drag: bool,
+
+    /// The pointer button currently held down, if any, when [`Self::single_pointer_button`] is on.
+    held_pointer_button: Option<egui::PointerButton>,
+
+    /// Where and with which button the current drag started.
+    drag_start: Option<(egui::Pos2, egui::PointerButton)>,
+
+    /// Raw mouse motion accumulated since the last [`Self::take_raw_mouse_delta`].
+    raw_mouse_delta: egui::Vec2,
+
+    /// Trackpad rotation in radians, accumulated since the last [`Self::take_egui_input`].
+    pending_rotation_delta: f32,
+
+    /// Trackpad rotation in radians during the current frame, until taken.
+    rotation_delta: f32,
+
+    /// Latest trackpad `(pressure, stage)` since the last [`Self::take_egui_input`].
+    pending_touchpad_pressure: Option<(f32, i64)>,
+
+    /// Latest trackpad `(pressure, stage)` as of the current frame, until taken.
+    touchpad_pressure: Option<(f32, i64)>,
+
+    /// The `pixels_per_point` of the window, as of the last frame or `ScaleFactorChanged`.
+    pixels_per_point: f32,
+
+    /// See [`Self::window_scale_factor`].
+    window_scale_factor: f32,
+
+    /// Set when [`Self::pixels_per_point`] changes, until taken.
+    pixels_per_point_changed: Option<f32>,
+
+    /// Wait for `ScaleFactorChanged` events to settle down this long before telling egui.
+    scale_change_debounce: Option<std::time::Duration>,
+    last_scale_change: Option<web_time::Instant>,
+
+    /// The native pixels per point egui was last told about.
+    egui_native_pixels_per_point: Option<f32>,
+
     current_cursor_icon: Option<egui::CursorIcon>,
 
-    clipboard: clipboard::Clipboard,
+    /// If `true`, only set the cursor icon when it changes.
+    dedup_cursor_sets: bool,
 
-    /// If `true`, mouse inputs will be treated as touches.
-    /// Useful for debugging touch support in egui.
+    /// Called when a new cursor icon is applied.
+    cursor_change_callback: Option<Box<dyn FnMut(egui::CursorIcon) + Send + Sync>>,
+
+    /// The last icon given to [`Self::cursor_change_callback`].
     ///
-    /// Creates duplicate touches, if real touch inputs are coming.
-    simulate_touch_screen: bool,
+    /// Unlike [`Self::current_cursor_icon`] this is not reset when the pointer leaves the window.
+    last_reported_cursor_icon: Option<egui::CursorIcon>,
+
+    /// If `true`, URLs are queued in [`Self::pending_url`] instead of being opened right away.
+    defer_url_open: bool,
+
+    pending_url: Option<egui::output::OpenUrl>,
+
+    /// Opens clicked URLs instead of the web browser.
+    open_url_handler: Option<Box<dyn Fn(&egui::OpenUrl) + Send + Sync>>,
+
+    /// If `true`, [`Self::on_window_event`] never asks for a repaint.
+    trust_egui_repaint: bool,
+
+    clipboard: Box<dyn clipboard::ClipboardProvider>,
+
+    /// When mouse inputs should be treated as touches.
+    touch_mode: TouchMode,
+
+    /// Have we received any real [`winit::event::WindowEvent::Touch`]?
+    seen_real_touch: bool,
 
     /// Is Some(…) when a touch is being translated to a pointer.
     ///
     /// Only one touch will be interpreted as pointer at any time.
     pointer_touch_id: Option<u64>,
 
+    /// The first two touches that are down, and where they were last seen, for pinch-to-zoom.
+    pinch_touches: Vec<(u64, egui::Pos2)>,
+
     /// track ime state
     input_method_editor_started: bool,
 
+    /// The keys currently held down.
+    pressed_keys: egui::ahash::HashSet<egui::Key>,
+
+    /// If `true`, IME commits are also sent as [`egui::Event::Text`].
+    ime_commit_as_text: bool,
+
+    /// If `true`, key presses that are part of a dead-key sequence are not sent to egui.
+    suppress_dead_keys: bool,
+
+    /// We are in the middle of a dead-key sequence, waiting for the composed character.
+    dead_key_composing: bool,
+
+    /// Keys pressed during a dead-key sequence, whose release should also be suppressed.
+    dead_key_suppressed: egui::ahash::HashSet<egui::Key>,
+
+    last_paste_source: Option<PasteSource>,
+
+    /// The phase of the last scroll gesture, if known.
+    last_scroll_phase: Option<egui::TouchPhase>,
+
+    /// How many files were dropped in the last batch of dropped files.
+    last_drop_count: usize,
+
+    /// If set, [`winit::event::WindowEvent::AxisMotion`] moves the pointer.
+    axis_pointer: Option<AxisPointerConfig>,
+
+    /// If set, held navigation keys scroll faster and faster.
+    key_repeat_acceleration: Option<AccelCurve>,
+
+    /// The navigation key currently held down, and when it was first pressed.
+    key_hold_start: Option<(egui::Key, web_time::Instant)>,
+
+    /// If `true`, moving the mouse over the window gives egui keyboard focus.
+    focus_follows_mouse: bool,
+
+    /// The light/dark preference of the OS, if known.
+    system_theme: Option<egui::SystemTheme>,
+
+    /// If `true`, switch the egui visuals when [`Self::system_theme`] changes.
+    follow_system_theme: bool,
+
+    /// Coalesce repaints caused by `Resized` events to at most one per this interval.
+    resize_repaint_throttle: Option<std::time::Duration>,
+    last_resize_repaint: Option<web_time::Instant>,
+
+    /// If `true`, the pointer is considered gone when the window is occluded.
+    clear_pointer_on_occlude: bool,
+
+    /// When we last got a real user input event (pointer, keyboard, touch, …).
+    last_input_instant: Option<web_time::Instant>,
+
+    /// If set, `TouchpadPressure` is forwarded to egui, and force clicks trigger this action.
+    force_touch: Option<ForceClickAction>,
+
+    touchpad_pressure_stage: i64,
+
+    /// If set, this is pushed to egui on the "smart zoom" trackpad gesture.
+    smart_magnify_event: Option<egui::Event>,
+
+    platform_output_options: PlatformOutputOptions,
+
+    /// Cached from the [`egui::Context`] in [`Self::take_egui_input`].
+    wants_pointer_input: bool,
+    wants_keyboard_input: bool,
+    is_using_pointer: bool,
+
+    text_nav_style: TextNavStyle,
+
+    /// How many points to scroll per line of a notched mouse wheel.
+    points_per_scroll_line: f32,
+
+    /// If `true`, scrolling with ctrl/cmd held zooms instead.
+    zoom_with_ctrl_scroll: bool,
+
+    /// Scrolling this many points with ctrl/cmd held zooms by a factor of `e`.
+    ctrl_scroll_zoom_divisor: f32,
+
+    /// If `false`, never send [`egui::Event::Zoom`] to egui.
+    allow_gui_zoom: bool,
+
+    copy_newline_style: NewlineStyle,
+
+    /// Holding these modifiers turns vertical scrolling into horizontal scrolling.
+    horizontal_scroll_modifier: Option<egui::Modifiers>,
+
+    /// Applied to the normalized force of touches.
+    force_curve: Option<Box<dyn Fn(f32) -> f32 + Send + Sync>>,
+
+    /// Gets to see all keyboard input before egui does. Returns `true` to consume it.
+    keyboard_capture: Option<Box<dyn FnMut(&winit::event::KeyboardInput) -> bool + Send + Sync>>,
+
+    /// Gets the keys egui has no [`egui::Key`] for.
+    raw_keycode_sink: Option<Box<dyn Fn(winit::event::VirtualKeyCode, bool) + Send + Sync>>,
+
     #[cfg(feature = "accesskit")]
     accesskit: Option<accesskit_winit::Adapter>,
 
     allow_ime: bool,
 }
 
+/// Configures a [`State`] up front, instead of with setters after construction.
+///
+/// Created with [`State::builder`].
+#[must_use]
+pub struct StateBuilder<'a> {
+    egui_ctx: egui::Context,
+    viewport_id: ViewportId,
+    display_target: &'a dyn HasRawDisplayHandle,
+    native_pixels_per_point: Option<f32>,
+    max_texture_side: Option<usize>,
+    touch_mode: TouchMode,
+}
+
+impl<'a> StateBuilder<'a> {
+    /// The scale factor of the window the [`State`] is for.
+    ///
+    /// Default: `None`, i.e. 1.0 until winit reports the real one.
+    #[inline]
+    pub fn with_pixels_per_point(mut self, native_pixels_per_point: f32) -> Self {
+        self.native_pixels_per_point = Some(native_pixels_per_point);
+        self
+    }
+
+    /// See [`State::set_max_texture_side`].
+    ///
+    /// Default: `None`, i.e. unknown.
+    #[inline]
+    pub fn with_max_texture_side(mut self, max_texture_side: usize) -> Self {
+        self.max_texture_side = Some(max_texture_side);
+        self
+    }
+
+    /// See [`State::set_touch_mode`].
+    ///
+    /// Default: [`TouchMode::ForceMouse`].
+    #[inline]
+    pub fn with_touch_mode(mut self, touch_mode: TouchMode) -> Self {
+        self.touch_mode = touch_mode;
+        self
+    }
+
+    /// Construct the [`State`].
+    pub fn build(self) -> State {
+        let Self {
+            egui_ctx,
+            viewport_id,
+            display_target,
+            native_pixels_per_point,
+            max_texture_side,
+            touch_mode,
+        } = self;
+
+        let mut state = State::new_with_clipboard(
+            egui_ctx,
+            viewport_id,
+            Box::new(clipboard::Clipboard::new(display_target)),
+            native_pixels_per_point,
+            max_texture_side,
+        );
+        state.set_touch_mode(touch_mode);
+        state
+    }
+}
+
 impl State {
     /// Construct a new instance
+    ///
+    /// Use [`Self::builder`] to configure more up front.
     pub fn new(
         egui_ctx: egui::Context,
         viewport_id: ViewportId,
         display_target: &dyn HasRawDisplayHandle,
         native_pixels_per_point: Option<f32>,
         max_texture_side: Option<usize>,
+    ) -> Self {
+        let mut builder = Self::builder(egui_ctx, viewport_id, display_target);
+        builder.native_pixels_per_point = native_pixels_per_point;
+        builder.max_texture_side = max_texture_side;
+        builder.build()
+    }
+
+    /// Configure a new instance, e.g.
+    ///
+    /// ``` no_run
+    /// # let egui_ctx = egui::Context::default();
+    /// # let event_loop = winit::event_loop::EventLoop::new();
+    /// let state = egui_winit::State::builder(egui_ctx, egui::ViewportId::ROOT, &event_loop)
+    ///     .with_max_texture_side(8192)
+    ///     .with_touch_mode(egui_winit::TouchMode::ForceTouch)
+    ///     .build();
+    /// ```
+    pub fn builder(
+        egui_ctx: egui::Context,
+        viewport_id: ViewportId,
+        display_target: &dyn HasRawDisplayHandle,
+    ) -> StateBuilder<'_> {
+        StateBuilder {
+            egui_ctx,
+            viewport_id,
+            display_target,
+            native_pixels_per_point: None,
+            max_texture_side: None,
+            touch_mode: TouchMode::default(),
+        }
+    }
+
+    /// Construct a new instance without a display handle.
+    ///
+    /// The clipboard does nothing until you call [`Self::set_clipboard_display`].
+    /// This is for embedders that get the display handle later.
+    pub fn new_deferred(
+        egui_ctx: egui::Context,
+        viewport_id: ViewportId,
+        native_pixels_per_point: Option<f32>,
+        max_texture_side: Option<usize>,
+    ) -> Self {
+        Self::new_with_clipboard(
+            egui_ctx,
+            viewport_id,
+            Box::new(clipboard::Clipboard::deferred()),
+            native_pixels_per_point,
+            max_texture_side,
+        )
+    }
+
+    /// Construct a new instance that uses your own clipboard instead of the OS one,
+    /// e.g. when running sandboxed.
+    pub fn new_with_clipboard(
+        egui_ctx: egui::Context,
+        viewport_id: ViewportId,
+        clipboard: Box<dyn clipboard::ClipboardProvider>,
+        native_pixels_per_point: Option<f32>,
+        max_texture_side: Option<usize>,
     ) -> Self {
         crate::profile_function!();
 
@@ -117,6 +806,8 @@ impl State {
             ..Default::default()
         };
 
+        let pixels_per_point = egui_ctx.zoom_factor() * native_pixels_per_point.unwrap_or(1.0);
+
         let mut slf = Self {
             egui_ctx,
             viewport_id,
@@ -124,14 +815,88 @@ impl State {
             egui_input,
             pointer_pos_in_points: None,
             any_pointer_button_down: false,
+            single_pointer_button: false,
+            mouse_button_mapping: Default::default(),
+            round_pointer_to_pixels: false,
+            drag_sticky: false,
+            left_during_drag: false,
+            held_pointer_button: None,
+            drag_start: None,
+            raw_mouse_delta: egui::Vec2::ZERO,
+            pending_rotation_delta: 0.0,
+            rotation_delta: 0.0,
+            pending_touchpad_pressure: None,
+            touchpad_pressure: None,
+            pixels_per_point,
+            window_scale_factor: native_pixels_per_point.unwrap_or(1.0),
+            pixels_per_point_changed: None,
+            scale_change_debounce: None,
+            last_scale_change: None,
+            egui_native_pixels_per_point: native_pixels_per_point,
             current_cursor_icon: None,
-
-            clipboard: clipboard::Clipboard::new(display_target),
-
-            simulate_touch_screen: false,
+            dedup_cursor_sets: true,
+            cursor_change_callback: None,
+            last_reported_cursor_icon: None,
+            defer_url_open: false,
+            pending_url: None,
+            open_url_handler: None,
+            trust_egui_repaint: false,
+
+            clipboard,
+
+            touch_mode: TouchMode::default(),
+            seen_real_touch: false,
             pointer_touch_id: None,
+            pinch_touches: Vec::new(),
 
             input_method_editor_started: false,
+            pressed_keys: Default::default(),
+            ime_commit_as_text: false,
+            suppress_dead_keys: !cfg!(target_os = "macos"),
+            dead_key_composing: false,
+            dead_key_suppressed: Default::default(),
+            last_paste_source: None,
+            last_scroll_phase: None,
+            last_drop_count: 0,
+
+            axis_pointer: None,
+            key_repeat_acceleration: None,
+            key_hold_start: None,
+            focus_follows_mouse: false,
+            system_theme: None,
+            follow_system_theme: false,
+
+            resize_repaint_throttle: None,
+            last_resize_repaint: None,
+
+            clear_pointer_on_occlude: false,
+
+            last_input_instant: None,
+
+            force_touch: None,
+            smart_magnify_event: None,
+            touchpad_pressure_stage: 0,
+
+            platform_output_options: Default::default(),
+
+            wants_pointer_input: false,
+            wants_keyboard_input: false,
+            is_using_pointer: false,
+
+            text_nav_style: Default::default(),
+
+            points_per_scroll_line: POINTS_PER_SCROLL_LINE,
+            zoom_with_ctrl_scroll: true,
+            ctrl_scroll_zoom_divisor: CTRL_SCROLL_ZOOM_DIVISOR,
+            allow_gui_zoom: true,
+
+            copy_newline_style: NewlineStyle::Platform,
+
+            horizontal_scroll_modifier: None,
+
+            force_curve: None,
+            keyboard_capture: None,
+            raw_keycode_sink: None,
 
             #[cfg(feature = "accesskit")]
             accesskit: None,
@@ -172,1399 +937,5715 @@ impl State {
         self.egui_input.max_texture_side = Some(max_texture_side);
     }
 
-    #[inline]
-    pub fn egui_ctx(&self) -> &egui::Context {
-        &self.egui_ctx
-    }
-
-    /// The current input state.
-    /// This is changed by [`Self::on_window_event`] and cleared by [`Self::take_egui_input`].
-    #[inline]
-    pub fn egui_input(&self) -> &egui::RawInput {
-        &self.egui_input
+    /// Connect the clipboard to the given display,
+    /// e.g. after constructing with [`Self::new_deferred`].
+    pub fn set_clipboard_display(&mut self, display_target: &dyn HasRawDisplayHandle) {
+        self.clipboard.init_display(display_target);
     }
 
-    /// The current input state.
-    /// This is changed by [`Self::on_window_event`] and cleared by [`Self::take_egui_input`].
-    #[inline]
-    pub fn egui_input_mut(&mut self) -> &mut egui::RawInput {
-        &mut self.egui_input
+    /// Give up on reading or writing the OS clipboard after this long.
+    ///
+    /// See [`clipboard::Clipboard::set_timeout`].
+    /// Has no effect on a custom [`clipboard::ClipboardProvider`], unless it implements it.
+    pub fn set_clipboard_timeout(&mut self, timeout: Option<std::time::Duration>) {
+        self.clipboard.set_timeout(timeout);
     }
 
-    /// Prepare for a new frame by extracting the accumulated input,
+    /// Scroll faster the longer a navigation key (arrows, page up/down) is held down,
+    /// e.g. to get through long lists quickly.
     ///
-    /// as well as setting [the time](egui::RawInput::time) and [screen rectangle](egui::RawInput::screen_rect).
+    /// Each key repeat sends an extra [`egui::Event::Scroll`] on top of the key event itself,
+    /// unless egui wants keyboard input (e.g. a text field has focus).
     ///
-    /// You need to set [`egui::RawInput::viewports`] yourself though.
-    /// Use [`update_viewport_info`] to update the info for each
-    /// viewport.
-    pub fn take_egui_input(&mut self, window: &Window) -> egui::RawInput {
-        crate::profile_function!();
+    /// Default: `None`.
+    pub fn set_key_repeat_acceleration(&mut self, key_repeat_acceleration: Option<AccelCurve>) {
+        self.key_repeat_acceleration = key_repeat_acceleration;
+        self.key_hold_start = None;
+    }
 
-        self.egui_input.time = Some(self.start_time.elapsed().as_secs_f64());
+    /// Let [`winit::event::WindowEvent::AxisMotion`] move the pointer.
+    ///
+    /// This is for specialized input devices, e.g. accessibility joysticks.
+    /// The default is `None`, which ignores axis motion.
+    pub fn set_axis_pointer(&mut self, axis_pointer: Option<AxisPointerConfig>) {
+        self.axis_pointer = axis_pointer;
+    }
 
-        // On Windows, a minimized window will have 0 width and height.
-        // See: https://github.com/rust-windowing/winit/issues/208
-        // This solves an issue where egui window positions would be changed when minimizing on Windows.
-        let screen_size_in_pixels = screen_size_in_pixels(window);
-        let screen_size_in_points =
-            screen_size_in_pixels / pixels_per_point(&self.egui_ctx, window);
+    /// Read the light/dark preference of the OS from the window.
+    ///
+    /// Call this once after creating the window, as winit only sends
+    /// [`WindowEvent::ThemeChanged`] when the preference _changes_.
+    pub fn set_theme_from_window(&mut self, window: &Window) {
+        if let Some(theme) = window.theme() {
+            self.on_theme_changed(theme);
+        }
+    }
 
-        self.egui_input.screen_rect = (screen_size_in_points.x > 0.0
-            && screen_size_in_points.y > 0.0)
-            .then(|| Rect::from_min_size(Pos2::ZERO, screen_size_in_points));
+    /// The light/dark preference of the OS, e.g. to pick the initial visuals of the app.
+    ///
+    /// Either [`egui::SystemTheme::Light`] or [`egui::SystemTheme::Dark`],
+    /// or `None` if unknown (see [`Self::set_theme_from_window`]).
+    #[inline]
+    pub fn system_theme(&self) -> Option<egui::SystemTheme> {
+        self.system_theme
+    }
 
-        // Tell egui which viewport is now active:
-        self.egui_input.viewport_id = self.viewport_id;
+    /// If `true`, switch between [`egui::Visuals::light`] and [`egui::Visuals::dark`]
+    /// to match [`Self::system_theme`] whenever it is detected or changes.
+    ///
+    /// Default: `false`, so the app (or e.g. `eframe`) is in charge of the visuals.
+    pub fn set_follow_system_theme(&mut self, follow_system_theme: bool) {
+        self.follow_system_theme = follow_system_theme;
+        if let Some(theme) = self.system_theme.filter(|_| follow_system_theme) {
+            self.apply_system_theme(theme);
+        }
+    }
 
-        self.egui_input
-            .viewports
-            .entry(self.viewport_id)
-            .or_default()
-            .native_pixels_per_point = Some(window.scale_factor() as f32);
+    fn on_theme_changed(&mut self, theme: winit::window::Theme) {
+        let theme = translate_theme(theme);
+        self.system_theme = Some(theme);
+        if self.follow_system_theme {
+            self.apply_system_theme(theme);
+        }
+    }
 
-        self.egui_input.take()
+    fn apply_system_theme(&self, theme: egui::SystemTheme) {
+        self.egui_ctx.set_visuals(match theme {
+            egui::SystemTheme::Dark => egui::Visuals::dark(),
+            egui::SystemTheme::Light | egui::SystemTheme::SystemDefault => egui::Visuals::light(),
+        });
     }
 
-    /// Call this when there is a new event.
+    /// If `true`, moving the mouse over the window tells egui that the window is focused,
+    /// even if the OS never gave it keyboard focus.
     ///
-    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
-    pub fn on_window_event(
-        &mut self,
-        window: &Window,
-        event: &winit::event::WindowEvent<'_>,
-    ) -> EventResponse {
-        crate::profile_function!(short_window_event_description(event));
+    /// This is useful for kiosk and touch deployments without a keyboard or window manager,
+    /// where egui would otherwise never consider any widget focused.
+    ///
+    /// Moving egui focus to the hovered _widget_ requires cooperation from the app;
+    /// use [`Self::pointer_pos_in_points`] for that.
+    ///
+    /// Default: `false`.
+    pub fn set_focus_follows_mouse(&mut self, focus_follows_mouse: bool) {
+        self.focus_follows_mouse = focus_follows_mouse;
+    }
 
-        use winit::event::WindowEvent;
-        match event {
-            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                let native_pixels_per_point = *scale_factor as f32;
+    /// If `true`, [`EventResponse::repaint`] is always `false`,
+    /// and you decide when to repaint based only on egui's own repaint requests,
+    /// e.g. [`egui::Context::has_requested_repaint`] after running a frame.
+    ///
+    /// This avoids repainting on every mouse move for strictly reactive renderers.
+    /// The tradeoff is that egui can only ask for a repaint after it has run a frame
+    /// with the new input, so you are responsible for running egui after events
+    /// (without necessarily presenting the result),
+    /// as well as for painting the first frame and after resizes.
+    ///
+    /// Default: `false`.
+    pub fn set_trust_egui_repaint(&mut self, trust_egui_repaint: bool) {
+        self.trust_egui_repaint = trust_egui_repaint;
+    }
 
-                self.egui_input
-                    .viewports
-                    .entry(self.viewport_id)
-                    .or_default()
-                    .native_pixels_per_point = Some(native_pixels_per_point);
+    /// If `true`, [`Self::handle_platform_output`] only sets the cursor icon when it changes.
+    ///
+    /// This prevents flickering near the frame boundary when Windows tries to control
+    /// the cursor icon during window resizing, and saves some CPU elsewhere.
+    /// Turn it off if something else changes the cursor behind egui's back,
+    /// and you need egui to set it again every frame.
+    ///
+    /// Default: `true`.
+    pub fn set_dedup_cursor_sets(&mut self, dedup_cursor_sets: bool) {
+        self.dedup_cursor_sets = dedup_cursor_sets;
+    }
 
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            WindowEvent::MouseInput { state, button, .. } => {
-                self.on_mouse_button_input(*state, *button);
-                EventResponse {
-                    repaint: true,
-                    consumed: self.egui_ctx.wants_pointer_input(),
-                }
-            }
-            WindowEvent::MouseWheel { delta, .. } => {
-                self.on_mouse_wheel(window, *delta);
-                EventResponse {
-                    repaint: true,
-                    consumed: self.egui_ctx.wants_pointer_input(),
-                }
-            }
-            WindowEvent::CursorMoved { position, .. } => {
-                self.on_cursor_moved(window, *position);
-                EventResponse {
-                    repaint: true,
-                    consumed: self.egui_ctx.is_using_pointer(),
-                }
-            }
-            WindowEvent::CursorLeft { .. } => {
-                self.pointer_pos_in_points = None;
-                self.egui_input.events.push(egui::Event::PointerGone);
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            // WindowEvent::TouchpadPressure {device_id, pressure, stage, ..  } => {} // TODO
-            WindowEvent::Touch(touch) => {
-                self.on_touch(window, touch);
-                let consumed = match touch.phase {
-                    winit::event::TouchPhase::Started
-                    | winit::event::TouchPhase::Ended
-                    | winit::event::TouchPhase::Cancelled => self.egui_ctx.wants_pointer_input(),
-                    winit::event::TouchPhase::Moved => self.egui_ctx.is_using_pointer(),
-                };
-                EventResponse {
-                    repaint: true,
-                    consumed,
-                }
-            }
-            WindowEvent::ReceivedCharacter(ch) => {
-                // On Mac we get here when the user presses Cmd-C (copy), ctrl-W, etc.
-                // We need to ignore these characters that are side-effects of commands.
-                let is_mac_cmd = cfg!(target_os = "macos")
-                    && (self.egui_input.modifiers.ctrl || self.egui_input.modifiers.mac_cmd);
+    /// Call `callback` whenever [`Self::handle_platform_output`] changes the cursor icon,
+    /// e.g. for telemetry or to mirror the cursor on a hardware device.
+    ///
+    /// Not called when the icon is set again to the same icon,
+    /// even if the pointer left the window in between.
+    pub fn set_cursor_change_callback(
+        &mut self,
+        callback: impl FnMut(egui::CursorIcon) + Send + Sync + 'static,
+    ) {
+        self.cursor_change_callback = Some(Box::new(callback));
+    }
 
-                let consumed = if is_printable_char(*ch) && !is_mac_cmd {
-                    self.egui_input
-                        .events
-                        .push(egui::Event::Text(ch.to_string()));
-                    self.egui_ctx.wants_keyboard_input()
-                } else {
-                    false
-                };
-                EventResponse {
-                    repaint: true,
-                    consumed,
-                }
-            }
-            WindowEvent::Ime(ime) => {
-                // on Mac even Cmd-C is pressed during ime, a `c` is pushed to Preedit.
-                // So no need to check is_mac_cmd.
-                //
-                // How winit produce `Ime::Enabled` and `Ime::Disabled` differs in MacOS
-                // and Windows.
-                //
-                // - On Windows, before and after each Commit will produce an Enable/Disabled
-                // event.
-                // - On MacOS, only when user explicit enable/disable ime. No Disabled
-                // after Commit.
-                //
-                // We use input_method_editor_started to manually insert CompositionStart
-                // between Commits.
-                match ime {
-                    winit::event::Ime::Enabled | winit::event::Ime::Disabled => (),
-                    winit::event::Ime::Commit(text) => {
-                        self.input_method_editor_started = false;
-                        self.egui_input
-                            .events
-                            .push(egui::Event::CompositionEnd(text.clone()));
-                    }
-                    winit::event::Ime::Preedit(text, Some(_)) => {
-                        if !self.input_method_editor_started {
-                            self.input_method_editor_started = true;
-                            self.egui_input.events.push(egui::Event::CompositionStart);
-                        }
-                        self.egui_input
-                            .events
-                            .push(egui::Event::CompositionUpdate(text.clone()));
-                    }
-                    winit::event::Ime::Preedit(_, None) => {}
-                };
+    /// Don't open clicked URLs in [`Self::handle_platform_output`], but queue them
+    /// so you can open them after the frame with [`Self::take_pending_url`].
+    ///
+    /// Opening a browser can block or steal focus for a moment on some platforms,
+    /// which causes a hitch when it happens in the middle of a frame.
+    ///
+    /// Default: `false`.
+    pub fn set_defer_url_open(&mut self, defer_url_open: bool) {
+        self.defer_url_open = defer_url_open;
+    }
 
-                EventResponse {
-                    repaint: true,
-                    consumed: self.egui_ctx.wants_keyboard_input(),
-                }
-            }
-            WindowEvent::KeyboardInput { input, .. } => {
-                self.on_keyboard_input(input);
-                // When pressing the Tab key, egui focuses the first focusable element, hence Tab always consumes.
-                let consumed = self.egui_ctx.wants_keyboard_input()
-                    || input.virtual_keycode == Some(winit::event::VirtualKeyCode::Tab);
-                EventResponse {
-                    repaint: true,
-                    consumed,
-                }
-            }
-            WindowEvent::Focused(focused) => {
-                self.egui_input.focused = *focused;
-                // We will not be given a KeyboardInput event when the modifiers are released while
-                // the window does not have focus. Unset all modifier state to be safe.
-                self.egui_input.modifiers = egui::Modifiers::default();
-                self.egui_input
-                    .events
-                    .push(egui::Event::WindowFocused(*focused));
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            WindowEvent::HoveredFile(path) => {
-                self.egui_input.hovered_files.push(egui::HoveredFile {
-                    path: Some(path.clone()),
-                    ..Default::default()
-                });
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            WindowEvent::HoveredFileCancelled => {
-                self.egui_input.hovered_files.clear();
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            WindowEvent::DroppedFile(path) => {
-                self.egui_input.hovered_files.clear();
-                self.egui_input.dropped_files.push(egui::DroppedFile {
-                    path: Some(path.clone()),
-                    ..Default::default()
-                });
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
-            WindowEvent::ModifiersChanged(state) => {
-                self.egui_input.modifiers.alt = state.alt();
-                self.egui_input.modifiers.ctrl = state.ctrl();
-                self.egui_input.modifiers.shift = state.shift();
-                self.egui_input.modifiers.mac_cmd = cfg!(target_os = "macos") && state.logo();
-                self.egui_input.modifiers.command = if cfg!(target_os = "macos") {
-                    state.logo()
-                } else {
-                    state.ctrl()
-                };
-                EventResponse {
-                    repaint: true,
-                    consumed: false,
-                }
-            }
+    /// Open clicked URLs with this instead of the web browser,
+    /// e.g. to route them through your own navigation.
+    ///
+    /// The handler gets the whole [`egui::OpenUrl`], including whether the user asked for a new tab.
+    /// Not called for URLs held back by [`Self::set_defer_url_open`].
+    pub fn set_open_url_handler(
+        &mut self,
+        handler: impl Fn(&egui::OpenUrl) + Send + Sync + 'static,
+    ) {
+        self.open_url_handler = Some(Box::new(handler));
+    }
 
-            // Things that may require repaint:
-            WindowEvent::CursorEntered { .. }
-            | WindowEvent::Destroyed
-            | WindowEvent::Occluded(_)
-            | WindowEvent::Resized(_)
-            | WindowEvent::Moved(_)
-            | WindowEvent::ThemeChanged(_)
-            | WindowEvent::TouchpadPressure { .. }
-            | WindowEvent::CloseRequested => EventResponse {
-                repaint: true,
-                consumed: false,
-            },
+    /// The last URL that was clicked but not opened, because of [`Self::set_defer_url_open`].
+    pub fn take_pending_url(&mut self) -> Option<egui::output::OpenUrl> {
+        self.pending_url.take()
+    }
 
-            // Things we completely ignore:
-            WindowEvent::AxisMotion { .. }
-            | WindowEvent::SmartMagnify { .. }
-            | WindowEvent::TouchpadRotate { .. } => EventResponse {
-                repaint: false,
-                consumed: false,
-            },
+    /// The raw mouse motion accumulated by [`Self::on_device_event`] since the last call.
+    ///
+    /// This is in unspecified device units (not points), with no acceleration applied,
+    /// and keeps coming even when the cursor is grabbed or at the edge of the screen.
+    pub fn take_raw_mouse_delta(&mut self) -> egui::Vec2 {
+        std::mem::take(&mut self.raw_mouse_delta)
+    }
 
-            WindowEvent::TouchpadMagnify { delta, .. } => {
-                // Positive delta values indicate magnification (zooming in).
-                // Negative delta values indicate shrinking (zooming out).
-                let zoom_factor = (*delta as f32).exp();
-                self.egui_input.events.push(egui::Event::Zoom(zoom_factor));
-                EventResponse {
-                    repaint: true,
-                    consumed: self.egui_ctx.wants_pointer_input(),
-                }
-            }
-        }
+    /// How far the user rotated with two fingers on the trackpad (macOS only) this frame,
+    /// in radians, counter-clockwise.
+    ///
+    /// egui has no rotation event, so this is for apps that want it, e.g. to rotate an image.
+    /// The frame starts at [`Self::take_egui_input`]: rotation not taken by then is dropped.
+    pub fn take_rotation_delta(&mut self) -> f32 {
+        std::mem::take(&mut self.rotation_delta)
     }
 
-    /// Call this when there is a new [`accesskit::ActionRequest`].
+    /// The latest `(pressure, stage)` reported by a Force Touch trackpad (macOS only) this frame.
     ///
-    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
-    #[cfg(feature = "accesskit")]
-    pub fn on_accesskit_action_request(&mut self, request: accesskit::ActionRequest) {
-        self.egui_input
-            .events
-            .push(egui::Event::AccessKitActionRequest(request));
+    /// `pressure` is in `0.0..=1.0`. `stage` is `1` for a normal click and `2` for a deep press
+    /// ("force click"). Use this to trigger your own action on a deep press,
+    /// or see [`Self::set_force_touch`] to let egui-winit do it.
+    ///
+    /// Recorded whether or not [`Self::set_force_touch`] is enabled.
+    /// The frame starts at [`Self::take_egui_input`]: pressure not taken by then is dropped.
+    pub fn take_touchpad_pressure(&mut self) -> Option<(f32, i64)> {
+        self.touchpad_pressure.take()
     }
 
-    fn on_mouse_button_input(
-        &mut self,
-        state: winit::event::ElementState,
-        button: winit::event::MouseButton,
-    ) {
-        if let Some(pos) = self.pointer_pos_in_points {
-            if let Some(button) = translate_mouse_button(button) {
-                let pressed = state == winit::event::ElementState::Pressed;
+    /// Choose which parts of the [`egui::PlatformOutput`] [`Self::handle_platform_output`] applies.
+    pub fn set_platform_output_options(&mut self, options: PlatformOutputOptions) {
+        self.platform_output_options = options;
+    }
 
-                self.egui_input.events.push(egui::Event::PointerButton {
-                    pos,
-                    button,
-                    pressed,
-                    modifiers: self.egui_input.modifiers,
-                });
+    /// Which parts of the [`egui::PlatformOutput`] [`Self::handle_platform_output`] applies.
+    pub fn platform_output_options(&self) -> PlatformOutputOptions {
+        self.platform_output_options
+    }
 
-                if self.simulate_touch_screen {
-                    if pressed {
-                        self.any_pointer_button_down = true;
+    /// Override which keyboard conventions are used when translating modifier keys.
+    ///
+    /// Takes effect on the next [`WindowEvent::ModifiersChanged`].
+    ///
+    /// Default: [`TextNavStyle::default`] (that of the current platform).
+    pub fn set_text_nav_style(&mut self, text_nav_style: TextNavStyle) {
+        self.text_nav_style = text_nav_style;
+    }
 
-                        self.egui_input.events.push(egui::Event::Touch {
-                            device_id: egui::TouchDeviceId(0),
-                            id: egui::TouchId(0),
-                            phase: egui::TouchPhase::Start,
-                            pos,
-                            force: None,
-                        });
-                    } else {
-                        self.any_pointer_button_down = false;
+    /// Round the pointer position so that it lands on a whole physical pixel.
+    ///
+    /// At fractional scale factors (e.g. 1.25 or 1.5) this avoids hover effects and
+    /// other things placed at the pointer landing between pixels and looking blurry.
+    /// This gives up sub-pixel precision, e.g. for slow drags on some touchpads.
+    ///
+    /// Default: `false`.
+    pub fn set_round_pointer_to_pixels(&mut self, round_pointer_to_pixels: bool) {
+        self.round_pointer_to_pixels = round_pointer_to_pixels;
+    }
 
-                        self.egui_input.events.push(egui::Event::PointerGone);
+    /// Keep dragging when the pointer briefly leaves the window while a button is held.
+    ///
+    /// Normally [`winit::event::WindowEvent::CursorLeft`] makes egui forget the pointer,
+    /// which aborts any drag (e.g. of a slider). With this on, egui keeps the last
+    /// pointer position until the button is released, like browsers and native toolkits do.
+    ///
+    /// Default: `false`.
+    pub fn set_drag_sticky(&mut self, drag_sticky: bool) {
+        self.drag_sticky = drag_sticky;
+    }
 
-                        self.egui_input.events.push(egui::Event::Touch {
-                            device_id: egui::TouchDeviceId(0),
-                            id: egui::TouchId(0),
-                            phase: egui::TouchPhase::End,
-                            pos,
-                            force: None,
-                        });
-                    };
-                }
-            }
-        }
+    /// Only let one pointer button be held down at a time.
+    ///
+    /// While a button is held, presses and releases of other buttons are ignored.
+    /// This gives clean single-button interaction on touch-first UIs,
+    /// where a second finger could otherwise produce interleaved button events.
+    ///
+    /// Default: `false`.
+    pub fn set_single_pointer_button(&mut self, single_pointer_button: bool) {
+        self.single_pointer_button = single_pointer_button;
+        self.held_pointer_button = None;
     }
 
-    fn on_cursor_moved(
+    /// Map extra mouse buttons ([`winit::event::MouseButton::Other`]) to egui buttons.
+    ///
+    /// Mice with many buttons report them as `Other(n)`, and only `Other(1)` and `Other(2)`
+    /// map to [`egui::PointerButton::Extra1`] and [`egui::PointerButton::Extra2`] by default.
+    /// Entries here take precedence over that. Buttons that map to nothing are ignored.
+    ///
+    /// ```
+    /// # fn set(state: &mut egui_winit::State) {
+    /// state.set_mouse_button_mapping([
+    ///     (8, egui::PointerButton::Extra1),
+    ///     (9, egui::PointerButton::Extra2),
+    /// ]);
+    /// # }
+    /// ```
+    ///
+    /// Default: empty.
+    pub fn set_mouse_button_mapping(
         &mut self,
-        window: &Window,
-        pos_in_pixels: winit::dpi::PhysicalPosition<f64>,
+        mapping: impl IntoIterator<Item = (u16, egui::PointerButton)>,
     ) {
-        let pixels_per_point = pixels_per_point(&self.egui_ctx, window);
-
-        let pos_in_points = egui::pos2(
-            pos_in_pixels.x as f32 / pixels_per_point,
-            pos_in_pixels.y as f32 / pixels_per_point,
-        );
-        self.pointer_pos_in_points = Some(pos_in_points);
+        self.mouse_button_mapping = mapping.into_iter().collect();
+    }
 
-        if self.simulate_touch_screen {
-            if self.any_pointer_button_down {
-                self.egui_input
-                    .events
-                    .push(egui::Event::PointerMoved(pos_in_points));
-
-                self.egui_input.events.push(egui::Event::Touch {
-                    device_id: egui::TouchDeviceId(0),
-                    id: egui::TouchId(0),
-                    phase: egui::TouchPhase::Move,
-                    pos: pos_in_points,
-                    force: None,
-                });
-            }
-        } else {
-            self.egui_input
-                .events
-                .push(egui::Event::PointerMoved(pos_in_points));
-        }
+    /// How many points to scroll for each line (notch) of a mouse wheel,
+    /// both horizontally and vertically.
+    ///
+    /// Does not affect touchpads and other devices that report scrolling in pixels.
+    ///
+    /// Default: `50.0`.
+    pub fn set_points_per_scroll_line(&mut self, points_per_scroll_line: f32) {
+        self.points_per_scroll_line = points_per_scroll_line;
     }
 
-    fn on_touch(&mut self, window: &Window, touch: &winit::event::Touch) {
-        let pixels_per_point = pixels_per_point(&self.egui_ctx, window);
+    /// Should scrolling with ctrl (cmd on Mac) held be turned into [`egui::Event::Zoom`]?
+    ///
+    /// If `false`, it scrolls like normal.
+    ///
+    /// Default: `true`.
+    pub fn set_zoom_with_ctrl_scroll(&mut self, zoom_with_ctrl_scroll: bool) {
+        self.zoom_with_ctrl_scroll = zoom_with_ctrl_scroll;
+    }
 
-        // Emit touch event
-        self.egui_input.events.push(egui::Event::Touch {
-            device_id: egui::TouchDeviceId(egui::epaint::util::hash(touch.device_id)),
-            id: egui::TouchId::from(touch.id),
-            phase: match touch.phase {
-                winit::event::TouchPhase::Started => egui::TouchPhase::Start,
-                winit::event::TouchPhase::Moved => egui::TouchPhase::Move,
-                winit::event::TouchPhase::Ended => egui::TouchPhase::End,
-                winit::event::TouchPhase::Cancelled => egui::TouchPhase::Cancel,
-            },
-            pos: egui::pos2(
-                touch.location.x as f32 / pixels_per_point,
-                touch.location.y as f32 / pixels_per_point,
-            ),
-            force: match touch.force {
-                Some(winit::event::Force::Normalized(force)) => Some(force as f32),
-                Some(winit::event::Force::Calibrated {
-                    force,
-                    max_possible_force,
-                    ..
-                }) => Some((force / max_possible_force) as f32),
-                None => None,
-            },
-        });
-        // If we're not yet translating a touch or we're translating this very
-        // touch …
-        if self.pointer_touch_id.is_none() || self.pointer_touch_id.unwrap() == touch.id {
-            // … emit PointerButton resp. PointerMoved events to emulate mouse
-            match touch.phase {
-                winit::event::TouchPhase::Started => {
-                    self.pointer_touch_id = Some(touch.id);
-                    // First move the pointer to the right location
-                    self.on_cursor_moved(window, touch.location);
-                    self.on_mouse_button_input(
-                        winit::event::ElementState::Pressed,
-                        winit::event::MouseButton::Left,
-                    );
-                }
-                winit::event::TouchPhase::Moved => {
-                    self.on_cursor_moved(window, touch.location);
-                }
-                winit::event::TouchPhase::Ended => {
-                    self.pointer_touch_id = None;
-                    self.on_mouse_button_input(
-                        winit::event::ElementState::Released,
-                        winit::event::MouseButton::Left,
-                    );
-                    // The pointer should vanish completely to not get any
-                    // hover effects
-                    self.pointer_pos_in_points = None;
-                    self.egui_input.events.push(egui::Event::PointerGone);
-                }
-                winit::event::TouchPhase::Cancelled => {
-                    self.pointer_touch_id = None;
-                    self.pointer_pos_in_points = None;
-                    self.egui_input.events.push(egui::Event::PointerGone);
-                }
-            }
-        }
+    /// How sensitive zooming with ctrl (cmd on Mac) + scroll is.
+    ///
+    /// Scrolling this many points zooms by a factor of `e` (≈2.7),
+    /// so a larger value zooms more slowly.
+    ///
+    /// Default: `200.0`.
+    pub fn set_ctrl_scroll_zoom_divisor(&mut self, ctrl_scroll_zoom_divisor: f32) {
+        self.ctrl_scroll_zoom_divisor = ctrl_scroll_zoom_divisor;
     }
 
-    fn on_mouse_wheel(&mut self, window: &Window, delta: winit::event::MouseScrollDelta) {
-        let pixels_per_point = pixels_per_point(&self.egui_ctx, window);
+    /// If `false`, egui-winit never emits [`egui::Event::Zoom`]:
+    /// ctrl/cmd+scroll scrolls like normal and trackpad pinches are ignored.
+    ///
+    /// This is independent of egui's own keyboard zoom,
+    /// which is controlled by [`egui::Options::zoom_with_keyboard`].
+    ///
+    /// Default: `true`.
+    pub fn set_allow_gui_zoom(&mut self, allow_gui_zoom: bool) {
+        self.allow_gui_zoom = allow_gui_zoom;
+    }
 
-        {
-            let (unit, delta) = match delta {
-                winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                    (egui::MouseWheelUnit::Line, egui::vec2(x, y))
-                }
-                winit::event::MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition {
-                    x,
-                    y,
-                }) => (
-                    egui::MouseWheelUnit::Point,
-                    egui::vec2(x as f32, y as f32) / pixels_per_point,
-                ),
-            };
-            let modifiers = self.egui_input.modifiers;
-            self.egui_input.events.push(egui::Event::MouseWheel {
-                unit,
-                delta,
-                modifiers,
-            });
-        }
-        let delta = match delta {
-            winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                let points_per_scroll_line = 50.0; // Scroll speed decided by consensus: https://github.com/emilk/egui/issues/461
-                egui::vec2(x, y) * points_per_scroll_line
-            }
-            winit::event::MouseScrollDelta::PixelDelta(delta) => {
-                egui::vec2(delta.x as f32, delta.y as f32) / pixels_per_point
-            }
-        };
+    /// Which modifier keys turn vertical scrolling into horizontal scrolling.
+    ///
+    /// Scrolling is always sent to egui on both axes, so trackpads and mice with a tilt wheel
+    /// scroll horizontally without this. Use it for mice that can only scroll vertically,
+    /// e.g. `Some(egui::Modifiers::SHIFT)`.
+    /// Scroll events that already have a horizontal component are left alone.
+    ///
+    /// This takes precedence over zooming with ctrl/cmd+scroll.
+    /// `None` means scrolling is never turned horizontal.
+    ///
+    /// Default: `None`.
+    pub fn set_horizontal_scroll_modifier(&mut self, modifier: Option<egui::Modifiers>) {
+        self.horizontal_scroll_modifier = modifier;
+    }
 
-        if self.egui_input.modifiers.ctrl || self.egui_input.modifiers.command {
-            // Treat as zoom instead:
-            let factor = (delta.y / 200.0).exp();
-            self.egui_input.events.push(egui::Event::Zoom(factor));
-        } else if self.egui_input.modifiers.shift {
-            // Treat as horizontal scrolling.
-            // Note: one Mac we already get horizontal scroll events when shift is down.
-            self.egui_input
-                .events
-                .push(egui::Event::Scroll(egui::vec2(delta.x + delta.y, 0.0)));
-        } else {
-            self.egui_input.events.push(egui::Event::Scroll(delta));
-        }
+    /// Which line endings to use for text egui copies to the clipboard.
+    ///
+    /// Pasted text is always given to egui with `\n` line endings.
+    ///
+    /// Default: [`NewlineStyle::Platform`], so that e.g. Notepad on Windows shows the line breaks.
+    pub fn set_copy_newline_style(&mut self, copy_newline_style: NewlineStyle) {
+        self.copy_newline_style = copy_newline_style;
     }
 
-    fn on_keyboard_input(&mut self, input: &winit::event::KeyboardInput) {
-        if let Some(keycode) = input.virtual_keycode {
-            let pressed = input.state == winit::event::ElementState::Pressed;
+    /// Set a response curve for the force (pressure) of touches.
+    ///
+    /// The curve is given the normalized force in `0..=1` and should return the force egui sees,
+    /// e.g. `|force| force.powf(0.5)` to make light touches register more strongly.
+    /// Touches without force information are unaffected.
+    ///
+    /// Default: the identity.
+    pub fn set_force_curve(&mut self, force_curve: impl Fn(f32) -> f32 + Send + Sync + 'static) {
+        self.force_curve = Some(Box::new(force_curve));
+    }
 
-            if pressed {
-                // VirtualKeyCode::Paste etc in winit are broken/untrustworthy,
-                // so we detect these things manually:
-                if is_cut_command(self.egui_input.modifiers, keycode) {
-                    self.egui_input.events.push(egui::Event::Cut);
-                } else if is_copy_command(self.egui_input.modifiers, keycode) {
-                    self.egui_input.events.push(egui::Event::Copy);
-                } else if is_paste_command(self.egui_input.modifiers, keycode) {
-                    if let Some(contents) = self.clipboard.get() {
-                        let contents = contents.replace("\r\n", "\n");
-                        if !contents.is_empty() {
-                            self.egui_input.events.push(egui::Event::Paste(contents));
-                        }
-                    }
-                }
-            }
+    /// Capture all keyboard input, e.g. for a "press any key to bind" dialog.
+    ///
+    /// While set, every [`winit::event::WindowEvent::KeyboardInput`] is first given to `capture`,
+    /// including Tab, Escape and shortcuts.
+    /// If it returns `true` the key is consumed, and egui never sees it.
+    /// Note that the text typed by the key ([`winit::event::WindowEvent::ReceivedCharacter`])
+    /// is still sent to egui.
+    ///
+    /// Remove the capture again with [`Self::clear_keyboard_capture`].
+    pub fn set_keyboard_capture(
+        &mut self,
+        capture: impl FnMut(&winit::event::KeyboardInput) -> bool + Send + Sync + 'static,
+    ) {
+        self.keyboard_capture = Some(Box::new(capture));
+    }
 
-            if let Some(key) = translate_virtual_key_code(keycode) {
-                self.egui_input.events.push(egui::Event::Key {
-                    key,
-                    pressed,
-                    repeat: false, // egui will fill this in for us!
-                    modifiers: self.egui_input.modifiers,
-                });
-            }
-        }
+    /// Stop capturing keyboard input. See [`Self::set_keyboard_capture`].
+    pub fn clear_keyboard_capture(&mut self) {
+        self.keyboard_capture = None;
     }
 
-    /// Call with the output given by `egui`.
+    /// Receive the keys egui has no [`egui::Key`] for, e.g. media and browser keys
+    /// ([`winit::event::VirtualKeyCode::PlayPause`], [`winit::event::VirtualKeyCode::VolumeUp`],
+    /// [`winit::event::VirtualKeyCode::NavigateBackward`], …).
     ///
-    /// This will, if needed:
-    /// * update the cursor
-    /// * copy text to the clipboard
-    /// * open any clicked urls
-    /// * update the IME
-    /// *
-    pub fn handle_platform_output(
+    /// `sink` is called with the keycode and whether it was pressed (`true`) or released (`false`).
+    /// Keys that [`translate_virtual_key_code`] maps are sent to egui as usual, and never to `sink`.
+    ///
+    /// Remove the sink again with [`Self::clear_raw_keycode_sink`].
+    pub fn set_raw_keycode_sink(
         &mut self,
-        window: &Window,
-        platform_output: egui::PlatformOutput,
+        sink: impl Fn(winit::event::VirtualKeyCode, bool) + Send + Sync + 'static,
     ) {
-        crate::profile_function!();
+        self.raw_keycode_sink = Some(Box::new(sink));
+    }
 
-        let egui::PlatformOutput {
-            cursor_icon,
-            open_url,
-            copied_text,
-            events: _,                    // handled elsewhere
-            mutable_text_under_cursor: _, // only used in eframe web
-            text_cursor_pos,
-            #[cfg(feature = "accesskit")]
-            accesskit_update,
-        } = platform_output;
+    /// Stop receiving unmapped keys. See [`Self::set_raw_keycode_sink`].
+    pub fn clear_raw_keycode_sink(&mut self) {
+        self.raw_keycode_sink = None;
+    }
 
-        self.set_cursor_icon(window, cursor_icon);
+    /// Request the user's attention, e.g. by bouncing the dock icon or flashing the taskbar,
+    /// or stop doing so with [`egui::UserAttentionType::Reset`].
+    ///
+    /// The request is reset automatically once the window gains focus.
+    pub fn request_attention(&self, window: &Window, attention: egui::UserAttentionType) {
+        window.request_user_attention(translate_user_attention(attention));
+    }
 
-        if let Some(open_url) = open_url {
-            open_url_in_browser(&open_url.url);
+    /// Tell egui whether or not the window has keyboard focus.
+    ///
+    /// This is called for you on [`WindowEvent::Focused`],
+    /// but is useful when embedding egui in a host that doesn't forward focus events.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.egui_input.focused = focused;
+        // We will not be given a KeyboardInput event when the modifiers are released while
+        // the window does not have focus. Unset all modifier state to be safe.
+        self.egui_input.modifiers = egui::Modifiers::default();
+        if !focused {
+            // Same for other keys:
+            self.pressed_keys.clear();
+            self.key_hold_start = None;
+            self.dead_key_composing = false;
+            self.dead_key_suppressed.clear();
+
+            // Some platforms (e.g. some Wayland compositors) don't always tell us
+            // when a file drag is cancelled, so don't let the hover get stuck:
+            self.egui_input.hovered_files.clear();
         }
+        self.egui_input
+            .events
+            .push(egui::Event::WindowFocused(focused));
+    }
 
-        if !copied_text.is_empty() {
-            self.clipboard.set(copied_text);
-        }
+    /// Coalesce a storm of `ScaleFactorChanged` events, e.g. when slowly dragging a window
+    /// across the border between two monitors with different DPI.
+    ///
+    /// egui only gets told about the new scale factor once it hasn't changed for `debounce`,
+    /// so it doesn't rebuild its font atlas over and over.
+    /// [`Self::pixels_per_point`] is still updated right away.
+    ///
+    /// Default: `None` (tell egui right away).
+    pub fn set_scale_change_debounce(&mut self, debounce: Option<std::time::Duration>) {
+        self.scale_change_debounce = debounce;
+    }
 
-        let allow_ime = text_cursor_pos.is_some();
-        if self.allow_ime != allow_ime {
-            self.allow_ime = allow_ime;
-            window.set_ime_allowed(allow_ime);
-        }
+    /// Coalesce the repaints requested by a storm of `Resized` events
+    /// (e.g. during a live resize on Windows or Mac) to at most one per `throttle`.
+    ///
+    /// The final size is always painted: when a repaint is skipped,
+    /// a delayed repaint is requested from the [`egui::Context`] instead.
+    ///
+    /// Default: `None` (repaint on every resize).
+    pub fn set_resize_repaint_throttle(&mut self, throttle: Option<std::time::Duration>) {
+        self.resize_repaint_throttle = throttle;
+    }
 
-        if let Some(egui::Pos2 { x, y }) = text_cursor_pos {
-            window.set_ime_position(winit::dpi::LogicalPosition { x, y });
-        }
+    /// If `true`, egui is told that the pointer is gone when the window becomes occluded
+    /// (covered by another window), so that no stale hover effects remain.
+    ///
+    /// The pointer position is restored by the next `CursorMoved` event.
+    ///
+    /// Default: `false`.
+    pub fn set_clear_pointer_on_occlude(&mut self, clear_pointer_on_occlude: bool) {
+        self.clear_pointer_on_occlude = clear_pointer_on_occlude;
+    }
 
-        #[cfg(feature = "accesskit")]
-        if let Some(accesskit) = self.accesskit.as_ref() {
-            if let Some(update) = accesskit_update {
-                accesskit.update_if_active(|| update);
-            }
+    /// Enable support for Force Touch trackpads (Mac).
+    ///
+    /// When set, the trackpad pressure is forwarded to egui as the force of a touch
+    /// at the pointer position, and pressing hard enough for a "force click"
+    /// (pressure stage 2) triggers the given action.
+    ///
+    /// Default: `None` (ignore trackpad pressure).
+    pub fn set_force_touch(&mut self, force_click_action: Option<ForceClickAction>) {
+        self.force_touch = force_click_action;
+    }
+
+    /// Push this event to egui when the user double-taps the trackpad with two fingers
+    /// ("smart zoom"), e.g. to reset the zoom or toggle zoom-to-fit.
+    ///
+    /// Only macOS has this gesture.
+    ///
+    /// ```
+    /// # fn set(state: &mut egui_winit::State) {
+    /// // Reset the GUI zoom, as if the user pressed cmd+0:
+    /// let shortcut = egui::gui_zoom::kb_shortcuts::ZOOM_RESET;
+    /// state.set_smart_magnify_event(Some(egui::Event::Key {
+    ///     key: shortcut.key,
+    ///     pressed: true,
+    ///     repeat: false,
+    ///     modifiers: shortcut.modifiers,
+    /// }));
+    /// # }
+    /// ```
+    ///
+    /// Default: `None` (ignore the gesture).
+    pub fn set_smart_magnify_event(&mut self, event: Option<egui::Event>) {
+        self.smart_magnify_event = event;
+    }
+
+    /// When to treat mouse input as touch input.
+    ///
+    /// Turning off the simulation in the middle of a simulated touch cancels that touch.
+    ///
+    /// Default: [`TouchMode::ForceMouse`].
+    pub fn set_touch_mode(&mut self, touch_mode: TouchMode) {
+        let was_simulating = self.simulates_touch();
+        self.touch_mode = touch_mode;
+        if was_simulating && !self.simulates_touch() {
+            self.cancel_simulated_touch();
         }
     }
 
-    fn set_cursor_icon(&mut self, window: &Window, cursor_icon: egui::CursorIcon) {
-        if self.current_cursor_icon == Some(cursor_icon) {
-            // Prevent flickering near frame boundary when Windows OS tries to control cursor icon for window resizing.
-            // On other platforms: just early-out to save CPU.
+    /// See [`Self::set_touch_mode`].
+    #[inline]
+    pub fn touch_mode(&self) -> TouchMode {
+        self.touch_mode
+    }
+
+    /// Make sure egui doesn't think a simulated touch is still going on.
+    fn cancel_simulated_touch(&mut self) {
+        if !self.any_pointer_button_down {
             return;
         }
+        self.any_pointer_button_down = false;
 
-        let is_pointer_in_window = self.pointer_pos_in_points.is_some();
-        if is_pointer_in_window {
-            self.current_cursor_icon = Some(cursor_icon);
+        self.egui_input.events.push(egui::Event::PointerGone);
+        self.egui_input.events.push(egui::Event::Touch {
+            device_id: SIMULATED_TOUCH_DEVICE_ID,
+            id: SIMULATED_TOUCH_ID,
+            phase: egui::TouchPhase::Cancel,
+            pos: self.pointer_pos_in_points.unwrap_or_default(),
+            force: None,
+        });
+    }
 
-            if let Some(winit_cursor_icon) = translate_cursor(cursor_icon) {
-                window.set_cursor_visible(true);
-                window.set_cursor_icon(winit_cursor_icon);
-            } else {
-                window.set_cursor_visible(false);
-            }
-        } else {
-            // Remember to set the cursor again once the cursor returns to the screen:
-            self.current_cursor_icon = None;
+    fn simulates_touch(&self) -> bool {
+        match self.touch_mode {
+            TouchMode::Auto => !self.seen_real_touch,
+            TouchMode::ForceTouch => true,
+            TouchMode::ForceMouse => false,
         }
     }
-}
-
-/// Update the given viewport info with the current state of the window.
-///
-/// Call before [`State::take_egui_input`].
-pub fn update_viewport_info(
-    viewport_info: &mut ViewportInfo,
-    egui_ctx: &egui::Context,
-    window: &Window,
-) {
-    crate::profile_function!();
 
-    let pixels_per_point = pixels_per_point(egui_ctx, window);
+    /// Re-zero the clock used for [`egui::RawInput::time`].
+    ///
+    /// After days of uptime the time grows large enough that animation math
+    /// starts losing precision. Call this occasionally in always-on apps to avoid that.
+    ///
+    /// Note that this causes a discontinuity: the next frame will report a time close to zero,
+    /// so any animation in progress will jump to its end.
+    pub fn reset_time_origin(&mut self) {
+        self.start_time = web_time::Instant::now();
+    }
 
-    let has_a_position = match window.is_minimized() {
-        None | Some(true) => false,
-        Some(false) => true,
-    };
+    /// When the clock used for [`egui::RawInput::time`] started.
+    ///
+    /// [`egui::RawInput::time`] is the number of seconds since this instant.
+    #[inline]
+    pub fn start_time(&self) -> web_time::Instant {
+        self.start_time
+    }
 
-    let inner_pos_px = if has_a_position {
-        window
-            .inner_position()
-            .map(|pos| Pos2::new(pos.x as f32, pos.y as f32))
-            .ok()
-    } else {
-        None
-    };
+    /// Set when the clock used for [`egui::RawInput::time`] started,
+    /// e.g. to align egui's time with an audio timeline.
+    ///
+    /// Note that changing this while running causes a discontinuity in time,
+    /// so any animation in progress may jump.
+    pub fn set_start_time(&mut self, start_time: web_time::Instant) {
+        self.start_time = start_time;
+    }
 
-    let outer_pos_px = if has_a_position {
-        window
-            .outer_position()
-            .map(|pos| Pos2::new(pos.x as f32, pos.y as f32))
-            .ok()
-    } else {
-        None
-    };
+    /// The value for [`egui::RawInput::time`] right now.
+    fn current_time(&self) -> f64 {
+        self.start_time.elapsed().as_secs_f64()
+    }
 
-    let inner_size_px = if has_a_position {
-        let size = window.inner_size();
-        Some(Vec2::new(size.width as f32, size.height as f32))
-    } else {
-        None
-    };
+    #[inline]
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
 
-    let outer_size_px = if has_a_position {
-        let size = window.outer_size();
-        Some(Vec2::new(size.width as f32, size.height as f32))
-    } else {
-        None
-    };
+    /// The `pixels_per_point` used for the last frame, including the egui zoom factor.
+    ///
+    /// See also [`Self::window_scale_factor`].
+    ///
+    /// Updated by [`Self::take_egui_input`] and on [`winit::event::WindowEvent::ScaleFactorChanged`].
+    #[inline]
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
 
-    let inner_rect_px = if let (Some(pos), Some(size)) = (inner_pos_px, inner_size_px) {
-        Some(Rect::from_min_size(pos, size))
-    } else {
-        None
-    };
+    /// Returns the new [`Self::pixels_per_point`] if it has changed since the last call,
+    /// e.g. because of a DPI change or the egui zoom factor changing.
+    ///
+    /// Useful for knowing when to rebuild DPI-dependent resources, like render targets.
+    pub fn take_pixels_per_point_changed(&mut self) -> Option<f32> {
+        self.pixels_per_point_changed.take()
+    }
 
-    let outer_rect_px = if let (Some(pos), Some(size)) = (outer_pos_px, outer_size_px) {
-        Some(Rect::from_min_size(pos, size))
-    } else {
-        None
-    };
+    /// The scale factor of the window, as reported by the OS (winit),
+    /// i.e. the number of physical pixels per logical pixel.
+    ///
+    /// Unlike [`Self::pixels_per_point`] this does not include the egui zoom factor:
+    ///
+    /// `pixels_per_point = window_scale_factor * zoom_factor`
+    ///
+    /// Updated by [`Self::take_egui_input`] and on [`winit::event::WindowEvent::ScaleFactorChanged`].
+    #[inline]
+    pub fn window_scale_factor(&self) -> f32 {
+        self.window_scale_factor
+    }
 
-    let inner_rect = inner_rect_px.map(|r| r / pixels_per_point);
-    let outer_rect = outer_rect_px.map(|r| r / pixels_per_point);
+    fn set_window_scale_factor(&mut self, window_scale_factor: f32) {
+        self.window_scale_factor = window_scale_factor;
+        self.set_pixels_per_point(self.egui_ctx.zoom_factor() * window_scale_factor);
+    }
 
-    let monitor_size = {
-        crate::profile_scope!("monitor_size");
-        if let Some(monitor) = window.current_monitor() {
-            let size = monitor.size().to_logical::<f32>(pixels_per_point.into());
-            Some(egui::vec2(size.width, size.height))
-        } else {
-            None
+    fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        if self.pixels_per_point != pixels_per_point {
+            self.pixels_per_point = pixels_per_point;
+            self.pixels_per_point_changed = Some(pixels_per_point);
         }
-    };
+    }
 
-    viewport_info.focused = Some(window.has_focus());
-    viewport_info.fullscreen = Some(window.fullscreen().is_some());
-    viewport_info.inner_rect = inner_rect;
-    viewport_info.monitor_size = monitor_size;
-    viewport_info.native_pixels_per_point = Some(window.scale_factor() as f32);
-    viewport_info.outer_rect = outer_rect;
-    viewport_info.title = Some(window.title());
+    /// The size of the window contents, in physical pixels.
+    pub fn screen_size_in_pixels(&self, window: &Window) -> egui::Vec2 {
+        screen_size_in_pixels(window)
+    }
 
-    if false {
-        // It's tempting to do this, but it leads to a deadlock on Mac when running
-        // `cargo run -p custom_window_frame`.
-        // See https://github.com/emilk/egui/issues/3494
-        viewport_info.maximized = Some(window.is_maximized());
-        viewport_info.minimized = window.is_minimized().or(viewport_info.minimized);
+    /// The size of the window contents, in egui points.
+    ///
+    /// Uses the cached [`Self::pixels_per_point`], so the result matches what egui was told.
+    pub fn screen_size_in_points(&self, window: &Window) -> egui::Vec2 {
+        self.pixels_to_points(screen_size_in_pixels(window))
     }
-}
 
-fn open_url_in_browser(_url: &str) {
-    #[cfg(feature = "webbrowser")]
-    if let Err(err) = webbrowser::open(_url) {
-        log::warn!("Failed to open url: {}", err);
+    fn pixels_to_points(&self, size_in_pixels: egui::Vec2) -> egui::Vec2 {
+        size_in_pixels / self.pixels_per_point
     }
 
-    #[cfg(not(feature = "webbrowser"))]
-    {
-        log::warn!("Cannot open url - feature \"links\" not enabled.");
+    /// The ongoing drag, if a pointer button is held down: `(start, current, button)`,
+    /// with the positions in egui points.
+    ///
+    /// If several buttons are held, this is the drag of the first one pressed.
+    /// Returns `None` while the pointer is outside the window.
+    pub fn current_drag(&self) -> Option<(egui::Pos2, egui::Pos2, egui::PointerButton)> {
+        let (start, button) = self.drag_start?;
+        let current = self.pointer_pos_in_points?;
+        Some((start, current, button))
     }
-}
 
-/// Winit sends special keys (backspace, delete, F1, …) as characters.
-/// Ignore those.
-/// We also ignore '\r', '\n', '\t'.
-/// Newlines are handled by the `Key::Enter` event.
-fn is_printable_char(chr: char) -> bool {
-    let is_in_private_use_area = '\u{e000}' <= chr && chr <= '\u{f8ff}'
-        || '\u{f0000}' <= chr && chr <= '\u{ffffd}'
-        || '\u{100000}' <= chr && chr <= '\u{10fffd}';
+    /// The files currently being dragged over the window.
+    #[inline]
+    pub fn hovered_files(&self) -> &[egui::HoveredFile] {
+        &self.egui_input.hovered_files
+    }
 
-    !is_in_private_use_area && !chr.is_ascii_control()
-}
+    /// Forget about any files being dragged over the window.
+    ///
+    /// Useful if the platform doesn't tell us when a drag is cancelled,
+    /// leaving a drop overlay stuck on screen.
+    /// This is done automatically when the window loses focus.
+    pub fn clear_hovered_files(&mut self) {
+        self.egui_input.hovered_files.clear();
+    }
 
-fn is_cut_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
-    (modifiers.command && keycode == winit::event::VirtualKeyCode::X)
-        || (cfg!(target_os = "windows")
-            && modifiers.shift
-            && keycode == winit::event::VirtualKeyCode::Delete)
-}
+    /// How many files the user dropped on the window in the most recent drop.
+    ///
+    /// All files dropped between two calls to [`Self::take_egui_input`] count as one drop,
+    /// and are all found in [`egui::RawInput::dropped_files`].
+    #[inline]
+    pub fn last_drop_count(&self) -> usize {
+        self.last_drop_count
+    }
 
-fn is_copy_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
-    (modifiers.command && keycode == winit::event::VirtualKeyCode::C)
-        || (cfg!(target_os = "windows")
-            && modifiers.ctrl
-            && keycode == winit::event::VirtualKeyCode::Insert)
-}
+    /// The modifier keys currently held down.
+    ///
+    /// Unlike the rest of [`Self::egui_input`], this is not cleared by [`Self::take_egui_input`].
+    #[inline]
+    pub fn modifiers(&self) -> egui::Modifiers {
+        self.egui_input.modifiers
+    }
 
-fn is_paste_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
-    (modifiers.command && keycode == winit::event::VirtualKeyCode::V)
-        || (cfg!(target_os = "windows")
-            && modifiers.shift
-            && keycode == winit::event::VirtualKeyCode::Insert)
-}
+    /// The keys egui-winit believes are currently held down, in no particular order.
+    ///
+    /// All keys are considered released when the window loses focus.
+    pub fn pressed_keys(&self) -> impl Iterator<Item = egui::Key> + '_ {
+        self.pressed_keys.iter().copied()
+    }
 
-fn translate_mouse_button(button: winit::event::MouseButton) -> Option<egui::PointerButton> {
-    match button {
-        winit::event::MouseButton::Left => Some(egui::PointerButton::Primary),
-        winit::event::MouseButton::Right => Some(egui::PointerButton::Secondary),
-        winit::event::MouseButton::Middle => Some(egui::PointerButton::Middle),
-        winit::event::MouseButton::Other(1) => Some(egui::PointerButton::Extra1),
-        winit::event::MouseButton::Other(2) => Some(egui::PointerButton::Extra2),
-        winit::event::MouseButton::Other(_) => None,
+    /// Is the user in the middle of composing text with an IME (Input Method Editor)?
+    ///
+    /// This is `true` between [`egui::Event::CompositionStart`] and [`egui::Event::CompositionEnd`].
+    #[inline]
+    pub fn is_composing(&self) -> bool {
+        self.input_method_editor_started
     }
-}
 
-fn translate_virtual_key_code(key: winit::event::VirtualKeyCode) -> Option<egui::Key> {
-    use egui::Key;
-    use winit::event::VirtualKeyCode;
+    /// Move the mouse cursor to `pos`, given in egui points.
+    ///
+    /// Positions outside the window are clamped to its inside,
+    /// so the cursor never ends up off-screen.
+    /// Not supported on all platforms (e.g. Wayland and the web).
+    pub fn warp_cursor(
+        &self,
+        window: &Window,
+        pos: egui::Pos2,
+    ) -> Result<(), winit::error::ExternalError> {
+        warp_cursor(window, self.pixels_per_point, pos)
+    }
 
-    Some(match key {
-        VirtualKeyCode::Down => Key::ArrowDown,
-        VirtualKeyCode::Left => Key::ArrowLeft,
-        VirtualKeyCode::Right => Key::ArrowRight,
-        VirtualKeyCode::Up => Key::ArrowUp,
+    /// Where the mouse pointer is, in egui points, or `None` if it is outside the window.
+    #[inline]
+    pub fn pointer_pos_in_points(&self) -> Option<egui::Pos2> {
+        self.pointer_pos_in_points
+    }
 
-        VirtualKeyCode::Escape => Key::Escape,
-        VirtualKeyCode::Tab => Key::Tab,
-        VirtualKeyCode::Back => Key::Backspace,
-        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
-        VirtualKeyCode::Space => Key::Space,
+    /// When the user last interacted with the window using the pointer, keyboard or touch.
+    ///
+    /// Window focus and scale changes, as well as input injected through
+    /// e.g. [`Self::on_menu_action`], do not count.
+    #[inline]
+    pub fn last_input_instant(&self) -> Option<web_time::Instant> {
+        self.last_input_instant
+    }
 
-        VirtualKeyCode::Insert => Key::Insert,
-        VirtualKeyCode::Delete => Key::Delete,
-        VirtualKeyCode::Home => Key::Home,
-        VirtualKeyCode::End => Key::End,
-        VirtualKeyCode::PageUp => Key::PageUp,
-        VirtualKeyCode::PageDown => Key::PageDown,
+    /// How long since the last user input, e.g. for starting a screen saver.
+    ///
+    /// If there has been no input yet, this is the time since the egui clock started.
+    pub fn idle_duration(&self) -> std::time::Duration {
+        self.last_input_instant.unwrap_or(self.start_time).elapsed()
+    }
 
-        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Key::Minus,
-        // Using Mac the key with the Plus sign on it is reported as the Equals key
-        // (with both English and Swedish keyboard).
-        VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => {
-            Key::PlusEquals
-        }
+    /// The current input state.
+    /// This is changed by [`Self::on_window_event`] and cleared by [`Self::take_egui_input`].
+    #[inline]
+    pub fn egui_input(&self) -> &egui::RawInput {
+        &self.egui_input
+    }
 
-        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Key::Num0,
-        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Key::Num1,
-        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Key::Num2,
-        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Key::Num3,
-        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Key::Num4,
-        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Key::Num5,
-        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Key::Num6,
-        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Key::Num7,
-        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Key::Num8,
-        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Key::Num9,
+    /// The current input state.
+    /// This is changed by [`Self::on_window_event`] and cleared by [`Self::take_egui_input`].
+    #[inline]
+    pub fn egui_input_mut(&mut self) -> &mut egui::RawInput {
+        &mut self.egui_input
+    }
 
-        VirtualKeyCode::A => Key::A,
-        VirtualKeyCode::B => Key::B,
-        VirtualKeyCode::C => Key::C,
-        VirtualKeyCode::D => Key::D,
-        VirtualKeyCode::E => Key::E,
-        VirtualKeyCode::F => Key::F,
-        VirtualKeyCode::G => Key::G,
-        VirtualKeyCode::H => Key::H,
-        VirtualKeyCode::I => Key::I,
-        VirtualKeyCode::J => Key::J,
-        VirtualKeyCode::K => Key::K,
-        VirtualKeyCode::L => Key::L,
-        VirtualKeyCode::M => Key::M,
-        VirtualKeyCode::N => Key::N,
-        VirtualKeyCode::O => Key::O,
-        VirtualKeyCode::P => Key::P,
-        VirtualKeyCode::Q => Key::Q,
-        VirtualKeyCode::R => Key::R,
-        VirtualKeyCode::S => Key::S,
-        VirtualKeyCode::T => Key::T,
-        VirtualKeyCode::U => Key::U,
-        VirtualKeyCode::V => Key::V,
-        VirtualKeyCode::W => Key::W,
-        VirtualKeyCode::X => Key::X,
-        VirtualKeyCode::Y => Key::Y,
-        VirtualKeyCode::Z => Key::Z,
+    /// Cached [`egui::Context::wants_pointer_input`].
+    ///
+    /// This reflects the last frame, as of the last call to [`Self::take_egui_input`].
+    /// Useful for deciding where to route an event without access to the [`egui::Context`].
+    #[inline]
+    pub fn wants_pointer_input(&self) -> bool {
+        self.wants_pointer_input
+    }
 
-        VirtualKeyCode::F1 => Key::F1,
-        VirtualKeyCode::F2 => Key::F2,
-        VirtualKeyCode::F3 => Key::F3,
-        VirtualKeyCode::F4 => Key::F4,
-        VirtualKeyCode::F5 => Key::F5,
-        VirtualKeyCode::F6 => Key::F6,
-        VirtualKeyCode::F7 => Key::F7,
-        VirtualKeyCode::F8 => Key::F8,
-        VirtualKeyCode::F9 => Key::F9,
-        VirtualKeyCode::F10 => Key::F10,
-        VirtualKeyCode::F11 => Key::F11,
-        VirtualKeyCode::F12 => Key::F12,
-        VirtualKeyCode::F13 => Key::F13,
-        VirtualKeyCode::F14 => Key::F14,
-        VirtualKeyCode::F15 => Key::F15,
-        VirtualKeyCode::F16 => Key::F16,
-        VirtualKeyCode::F17 => Key::F17,
-        VirtualKeyCode::F18 => Key::F18,
-        VirtualKeyCode::F19 => Key::F19,
-        VirtualKeyCode::F20 => Key::F20,
+    /// Cached [`egui::Context::wants_keyboard_input`].
+    ///
+    /// This reflects the last frame, as of the last call to [`Self::take_egui_input`].
+    #[inline]
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.wants_keyboard_input
+    }
 
-        _ => {
-            return None;
-        }
-    })
-}
+    /// Cached [`egui::Context::is_using_pointer`].
+    ///
+    /// This reflects the last frame, as of the last call to [`Self::take_egui_input`].
+    #[inline]
+    pub fn is_using_pointer(&self) -> bool {
+        self.is_using_pointer
+    }
 
-fn translate_cursor(cursor_icon: egui::CursorIcon) -> Option<winit::window::CursorIcon> {
-    match cursor_icon {
-        egui::CursorIcon::None => None,
+    fn cache_context_interaction(&mut self) {
+        self.wants_pointer_input = self.egui_ctx.wants_pointer_input();
+        self.wants_keyboard_input = self.egui_ctx.wants_keyboard_input();
+        self.is_using_pointer = self.egui_ctx.is_using_pointer();
+    }
 
-        egui::CursorIcon::Alias => Some(winit::window::CursorIcon::Alias),
-        egui::CursorIcon::AllScroll => Some(winit::window::CursorIcon::AllScroll),
-        egui::CursorIcon::Cell => Some(winit::window::CursorIcon::Cell),
-        egui::CursorIcon::ContextMenu => Some(winit::window::CursorIcon::ContextMenu),
-        egui::CursorIcon::Copy => Some(winit::window::CursorIcon::Copy),
-        egui::CursorIcon::Crosshair => Some(winit::window::CursorIcon::Crosshair),
-        egui::CursorIcon::Default => Some(winit::window::CursorIcon::Default),
-        egui::CursorIcon::Grab => Some(winit::window::CursorIcon::Grab),
-        egui::CursorIcon::Grabbing => Some(winit::window::CursorIcon::Grabbing),
-        egui::CursorIcon::Help => Some(winit::window::CursorIcon::Help),
-        egui::CursorIcon::Move => Some(winit::window::CursorIcon::Move),
-        egui::CursorIcon::NoDrop => Some(winit::window::CursorIcon::NoDrop),
-        egui::CursorIcon::NotAllowed => Some(winit::window::CursorIcon::NotAllowed),
-        egui::CursorIcon::PointingHand => Some(winit::window::CursorIcon::Hand),
-        egui::CursorIcon::Progress => Some(winit::window::CursorIcon::Progress),
+    /// Prepare for a new frame by extracting the accumulated input,
+    ///
+    /// as well as setting [the time](egui::RawInput::time) and [screen rectangle](egui::RawInput::screen_rect).
+    ///
+    /// You need to set [`egui::RawInput::viewports`] yourself though.
+    /// Use [`update_viewport_info`] to update the info for each
+    /// viewport.
+    pub fn take_egui_input(&mut self, window: &Window) -> egui::RawInput {
+        crate::profile_function!();
 
-        egui::CursorIcon::ResizeHorizontal => Some(winit::window::CursorIcon::EwResize),
-        egui::CursorIcon::ResizeNeSw => Some(winit::window::CursorIcon::NeswResize),
-        egui::CursorIcon::ResizeNwSe => Some(winit::window::CursorIcon::NwseResize),
-        egui::CursorIcon::ResizeVertical => Some(winit::window::CursorIcon::NsResize),
+        self.cache_context_interaction();
 
-        egui::CursorIcon::ResizeEast => Some(winit::window::CursorIcon::EResize),
-        egui::CursorIcon::ResizeSouthEast => Some(winit::window::CursorIcon::SeResize),
-        egui::CursorIcon::ResizeSouth => Some(winit::window::CursorIcon::SResize),
-        egui::CursorIcon::ResizeSouthWest => Some(winit::window::CursorIcon::SwResize),
-        egui::CursorIcon::ResizeWest => Some(winit::window::CursorIcon::WResize),
-        egui::CursorIcon::ResizeNorthWest => Some(winit::window::CursorIcon::NwResize),
-        egui::CursorIcon::ResizeNorth => Some(winit::window::CursorIcon::NResize),
-        egui::CursorIcon::ResizeNorthEast => Some(winit::window::CursorIcon::NeResize),
-        egui::CursorIcon::ResizeColumn => Some(winit::window::CursorIcon::ColResize),
-        egui::CursorIcon::ResizeRow => Some(winit::window::CursorIcon::RowResize),
+        self.egui_input.time = Some(self.current_time());
 
-        egui::CursorIcon::Text => Some(winit::window::CursorIcon::Text),
-        egui::CursorIcon::VerticalText => Some(winit::window::CursorIcon::VerticalText),
-        egui::CursorIcon::Wait => Some(winit::window::CursorIcon::Wait),
-        egui::CursorIcon::ZoomIn => Some(winit::window::CursorIcon::ZoomIn),
-        egui::CursorIcon::ZoomOut => Some(winit::window::CursorIcon::ZoomOut),
-    }
-}
+        self.latch_trackpad_gestures();
 
-// Helpers for egui Viewports
-// ---------------------------------------------------------------------------
+        // On Windows, a minimized window will have 0 width and height.
+        // See: https://github.com/rust-windowing/winit/issues/208
+        // This solves an issue where egui window positions would be changed when minimizing on Windows.
+        self.set_window_scale_factor(window.scale_factor() as f32);
+        let screen_size_in_points = self.screen_size_in_points(window);
 
-pub fn process_viewport_commands(
-    egui_ctx: &egui::Context,
-    info: &mut ViewportInfo,
-    commands: impl IntoIterator<Item = ViewportCommand>,
-    window: &Window,
-    is_viewport_focused: bool,
-    screenshot_requested: &mut bool,
-) {
-    for command in commands {
-        process_viewport_command(
-            egui_ctx,
-            window,
-            command,
-            info,
-            is_viewport_focused,
-            screenshot_requested,
-        );
-    }
-}
+        self.egui_input.screen_rect = (screen_size_in_points.x > 0.0
+            && screen_size_in_points.y > 0.0)
+            .then(|| Rect::from_min_size(Pos2::ZERO, screen_size_in_points));
 
-fn process_viewport_command(
-    egui_ctx: &egui::Context,
-    window: &Window,
-    command: ViewportCommand,
-    info: &mut ViewportInfo,
-    is_viewport_focused: bool,
-    screenshot_requested: &mut bool,
-) {
-    crate::profile_function!();
+        // Tell egui which viewport is now active:
+        self.egui_input.viewport_id = self.viewport_id;
 
-    use winit::window::ResizeDirection;
+        let native_pixels_per_point =
+            self.debounce_scale_change(window.scale_factor() as f32, web_time::Instant::now());
+        self.egui_input
+            .viewports
+            .entry(self.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(native_pixels_per_point);
 
-    log::debug!("Processing ViewportCommand::{command:?}");
+        self.take_raw_input()
+    }
 
-    let pixels_per_point = pixels_per_point(egui_ctx, window);
+    fn take_raw_input(&mut self) -> egui::RawInput {
+        let raw_input = self.egui_input.take();
 
-    match command {
-        ViewportCommand::Close => {
-            info.events.push(egui::ViewportEvent::Close);
-        }
-        ViewportCommand::CancelClose => {
-            // Need to be handled elsewhere
-        }
-        ViewportCommand::StartDrag => {
-            // If `is_viewport_focused` is not checked on x11 the input will be permanently taken until the app is killed!
+        // egui keeps the events we hand it, so we can't reuse their `Vec`.
+        // Instead, expect about as many events as last frame,
+        // so that a steady stream of input doesn't regrow the new `Vec` over and over.
+        self.egui_input.events.reserve(raw_input.events.len());
 
-            // TODO: check that the left mouse-button was pressed down recently,
-            // or we will have bugs on Windows.
-            // See https://github.com/emilk/egui/pull/1108
-            if is_viewport_focused {
-                if let Err(err) = window.drag_window() {
-                    log::warn!("{command:?}: {err}");
-                }
-            }
-        }
-        ViewportCommand::InnerSize(size) => {
-            let width_px = pixels_per_point * size.x.max(1.0);
-            let height_px = pixels_per_point * size.y.max(1.0);
-            window.set_inner_size(PhysicalSize::new(width_px, height_px));
-        }
-        ViewportCommand::BeginResize(direction) => {
-            if let Err(err) = window.drag_resize_window(match direction {
-                egui::viewport::ResizeDirection::North => ResizeDirection::North,
-                egui::viewport::ResizeDirection::South => ResizeDirection::South,
-                egui::viewport::ResizeDirection::West => ResizeDirection::West,
-                egui::viewport::ResizeDirection::NorthEast => ResizeDirection::NorthEast,
-                egui::viewport::ResizeDirection::SouthEast => ResizeDirection::SouthEast,
-                egui::viewport::ResizeDirection::NorthWest => ResizeDirection::NorthWest,
-                egui::viewport::ResizeDirection::SouthWest => ResizeDirection::SouthWest,
-            }) {
-                log::warn!("{command:?}: {err}");
+        raw_input
+    }
+
+    /// Returns the native pixels per point to tell egui about,
+    /// which lags behind `native_pixels_per_point` while it keeps changing.
+    fn debounce_scale_change(
+        &mut self,
+        native_pixels_per_point: f32,
+        now: web_time::Instant,
+    ) -> f32 {
+        if let (Some(debounce), Some(last_scale_change), Some(previous)) = (
+            self.scale_change_debounce,
+            self.last_scale_change,
+            self.egui_native_pixels_per_point,
+        ) {
+            let elapsed = now.saturating_duration_since(last_scale_change);
+            if previous != native_pixels_per_point && elapsed < debounce {
+                self.egui_ctx
+                    .request_repaint_after_for(debounce - elapsed, self.viewport_id);
+                return previous;
             }
         }
-        ViewportCommand::Title(title) => {
-            window.set_title(&title);
-        }
-        ViewportCommand::Transparent(v) => window.set_transparent(v),
-        ViewportCommand::Visible(v) => window.set_visible(v),
-        ViewportCommand::OuterPosition(pos) => {
-            window.set_outer_position(PhysicalPosition::new(
-                pixels_per_point * pos.x,
-                pixels_per_point * pos.y,
-            ));
-        }
-        ViewportCommand::MinInnerSize(s) => {
-            window.set_min_inner_size((s.is_finite() && s != Vec2::ZERO).then_some(
-                PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y),
-            ));
-        }
-        ViewportCommand::MaxInnerSize(s) => {
-            window.set_max_inner_size((s.is_finite() && s != Vec2::INFINITY).then_some(
-                PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y),
-            ));
-        }
-        ViewportCommand::ResizeIncrements(s) => {
-            window.set_resize_increments(
-                s.map(|s| PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y)),
-            );
-        }
-        ViewportCommand::Resizable(v) => window.set_resizable(v),
-        ViewportCommand::EnableButtons {
-            close,
-            minimized,
-            maximize,
-        } => window.set_enabled_buttons(
-            if close {
-                WindowButtons::CLOSE
-            } else {
-                WindowButtons::empty()
-            } | if minimized {
-                WindowButtons::MINIMIZE
-            } else {
-                WindowButtons::empty()
-            } | if maximize {
-                WindowButtons::MAXIMIZE
-            } else {
-                WindowButtons::empty()
-            },
-        ),
-        ViewportCommand::Minimized(v) => {
-            window.set_minimized(v);
-            info.minimized = Some(v);
-        }
-        ViewportCommand::Maximized(v) => {
-            window.set_maximized(v);
-            info.maximized = Some(v);
-        }
-        ViewportCommand::Fullscreen(v) => {
-            window.set_fullscreen(v.then_some(winit::window::Fullscreen::Borderless(None)));
-        }
-        ViewportCommand::Decorations(v) => window.set_decorations(v),
-        ViewportCommand::WindowLevel(l) => window.set_window_level(match l {
-            egui::viewport::WindowLevel::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
-            egui::viewport::WindowLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
-            egui::viewport::WindowLevel::Normal => WindowLevel::Normal,
-        }),
-        ViewportCommand::Icon(icon) => {
-            window.set_window_icon(icon.map(|icon| {
-                winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
-                    .expect("Invalid ICON data!")
-            }));
-        }
-        ViewportCommand::IMEPosition(pos) => {
-            window.set_ime_position(PhysicalPosition::new(
-                pixels_per_point * pos.x,
-                pixels_per_point * pos.y,
-            ));
+
+        self.egui_native_pixels_per_point = Some(native_pixels_per_point);
+        native_pixels_per_point
+    }
+
+    /// Call this when there is a new event.
+    ///
+    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
+    pub fn on_window_event(
+        &mut self,
+        window: &Window,
+        event: &winit::event::WindowEvent<'_>,
+    ) -> EventResponse {
+        crate::profile_function!(short_window_event_description(event));
+
+        let response = self.on_window_event_impl(window, event);
+        self.finish_response(event, response)
+    }
+
+    /// Apply [`Self::set_trust_egui_repaint`] and fill in [`EventResponse::consume_reason`].
+    fn finish_response(
+        &self,
+        event: &winit::event::WindowEvent<'_>,
+        mut response: EventResponse,
+    ) -> EventResponse {
+        if self.trust_egui_repaint {
+            response.repaint = false;
         }
-        ViewportCommand::IMEAllowed(v) => window.set_ime_allowed(v),
-        ViewportCommand::IMEPurpose(p) => window.set_ime_purpose(match p {
-            egui::viewport::IMEPurpose::Password => winit::window::ImePurpose::Password,
-            egui::viewport::IMEPurpose::Terminal => winit::window::ImePurpose::Terminal,
-            egui::viewport::IMEPurpose::Normal => winit::window::ImePurpose::Normal,
-        }),
-        ViewportCommand::Focus => {
-            if !window.has_focus() {
-                window.focus_window();
-            }
+        if response.consumed && response.consume_reason.is_none() {
+            response.consume_reason = self.consume_reason(event);
         }
-        ViewportCommand::RequestUserAttention(a) => {
-            window.request_user_attention(match a {
-                egui::UserAttentionType::Reset => None,
-                egui::UserAttentionType::Critical => {
-                    Some(winit::window::UserAttentionType::Critical)
-                }
-                egui::UserAttentionType::Informational => {
-                    Some(winit::window::UserAttentionType::Informational)
+        response
+    }
+
+    /// Why an event that was consumed by [`Self::on_window_event_impl`] was consumed.
+    fn consume_reason(&self, event: &winit::event::WindowEvent<'_>) -> Option<ConsumeReason> {
+        use winit::event::WindowEvent;
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if self.egui_ctx.wants_keyboard_input() {
+                    Some(ConsumeReason::KeyboardFocus)
+                } else {
+                    (input.virtual_keycode == Some(winit::event::VirtualKeyCode::Tab))
+                        .then_some(ConsumeReason::TabTraversal)
                 }
-            });
-        }
-        ViewportCommand::SetTheme(t) => window.set_theme(match t {
-            egui::SystemTheme::Light => Some(winit::window::Theme::Light),
-            egui::SystemTheme::Dark => Some(winit::window::Theme::Dark),
-            egui::SystemTheme::SystemDefault => None,
-        }),
-        ViewportCommand::ContentProtected(v) => window.set_content_protected(v),
-        ViewportCommand::CursorPosition(pos) => {
-            if let Err(err) = window.set_cursor_position(PhysicalPosition::new(
-                pixels_per_point * pos.x,
-                pixels_per_point * pos.y,
-            )) {
-                log::warn!("{command:?}: {err}");
-            }
-        }
-        ViewportCommand::CursorGrab(o) => {
-            if let Err(err) = window.set_cursor_grab(match o {
-                egui::viewport::CursorGrab::None => CursorGrabMode::None,
-                egui::viewport::CursorGrab::Confined => CursorGrabMode::Confined,
-                egui::viewport::CursorGrab::Locked => CursorGrabMode::Locked,
-            }) {
-                log::warn!("{command:?}: {err}");
             }
-        }
-        ViewportCommand::CursorVisible(v) => window.set_cursor_visible(v),
-        ViewportCommand::MousePassthrough(passthrough) => {
-            if let Err(err) = window.set_cursor_hittest(!passthrough) {
-                log::warn!("{command:?}: {err}");
+            WindowEvent::ReceivedCharacter(_) | WindowEvent::Ime(_) => {
+                Some(ConsumeReason::KeyboardFocus)
             }
+            WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::TouchpadPressure { .. }
+            | WindowEvent::TouchpadMagnify { .. }
+            | WindowEvent::SmartMagnify { .. }
+            | WindowEvent::AxisMotion { .. } => Some(self.pointer_consume_reason()),
+            _ => None,
         }
-        ViewportCommand::Screenshot => {
-            *screenshot_requested = true;
+    }
+
+    fn pointer_consume_reason(&self) -> ConsumeReason {
+        if self.egui_ctx.is_using_pointer() {
+            ConsumeReason::PointerInUse
+        } else {
+            ConsumeReason::PointerOverArea
         }
     }
-}
 
-/// Build and intitlaize a window.
-///
-/// Wrapper around `create_winit_window_builder` and `apply_viewport_builder_to_window`.
-pub fn create_window<T>(
-    egui_ctx: &egui::Context,
-    event_loop: &EventLoopWindowTarget<T>,
-    viewport_builder: &ViewportBuilder,
-) -> Result<Window, winit::error::OsError> {
-    crate::profile_function!();
+    fn on_window_event_impl(
+        &mut self,
+        window: &Window,
+        event: &winit::event::WindowEvent<'_>,
+    ) -> EventResponse {
+        self.on_user_input(event);
 
-    let window_builder =
-        create_winit_window_builder(egui_ctx, event_loop, viewport_builder.clone());
-    let window = {
-        crate::profile_scope!("WindowBuilder::build");
-        window_builder.build(event_loop)?
-    };
-    apply_viewport_builder_to_window(egui_ctx, &window, viewport_builder);
-    Ok(window)
-}
+        use winit::event::WindowEvent;
+        match event {
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                let native_pixels_per_point = *scale_factor as f32;
+                self.set_window_scale_factor(native_pixels_per_point);
 
-pub fn create_winit_window_builder<T>(
-    egui_ctx: &egui::Context,
-    event_loop: &EventLoopWindowTarget<T>,
-    viewport_builder: ViewportBuilder,
-) -> winit::window::WindowBuilder {
-    crate::profile_function!();
+                if let Some(debounce) = self.scale_change_debounce {
+                    // Applied in `take_egui_input` once the changes settle down:
+                    self.last_scale_change = Some(web_time::Instant::now());
+                    self.egui_ctx
+                        .request_repaint_after_for(debounce, self.viewport_id);
+                } else {
+                    self.egui_input
+                        .viewports
+                        .entry(self.viewport_id)
+                        .or_default()
+                        .native_pixels_per_point = Some(native_pixels_per_point);
+                }
 
-    // We set sizes and positions in egui:s own ui points, which depends on the egui
-    // zoom_factor and the native pixels per point, so we need to know that here.
-    // We don't know what monitor the window will appear on though, but
-    // we'll try to fix that after the window is created in the vall to `apply_viewport_builder_to_window`.
-    let native_pixels_per_point = event_loop
-        .primary_monitor()
-        .or_else(|| event_loop.available_monitors().next())
-        .map_or_else(
-            || {
-                log::debug!("Failed to find a monitor - assuming native_pixels_per_point of 1.0");
-                1.0
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.on_mouse_button_input(*state, *button);
+                EventResponse {
+                    repaint: true,
+                    consumed: self.egui_ctx.wants_pointer_input(),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                self.last_scroll_phase = scroll_phase(*delta, *phase);
+                self.on_mouse_wheel(*delta);
+                EventResponse {
+                    repaint: true,
+                    consumed: self.egui_ctx.wants_pointer_input(),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.on_hover_focus();
+                self.on_cursor_moved(window, *position);
+                EventResponse {
+                    repaint: true,
+                    consumed: self.egui_ctx.is_using_pointer(),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.on_cursor_left();
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::TouchpadPressure {
+                device_id,
+                pressure,
+                stage,
+            } => {
+                let handled = self.on_touchpad_pressure(*device_id, *pressure, *stage);
+                EventResponse {
+                    repaint: true,
+                    consumed: handled && self.egui_ctx.wants_pointer_input(),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                self.on_touch(touch);
+                let consumed = match touch.phase {
+                    winit::event::TouchPhase::Started
+                    | winit::event::TouchPhase::Ended
+                    | winit::event::TouchPhase::Cancelled => self.egui_ctx.wants_pointer_input(),
+                    winit::event::TouchPhase::Moved => self.egui_ctx.is_using_pointer(),
+                };
+                EventResponse {
+                    repaint: true,
+                    consumed,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::ReceivedCharacter(ch) => {
+                let consumed =
+                    self.on_received_character(*ch) && self.egui_ctx.wants_keyboard_input();
+                EventResponse {
+                    repaint: true,
+                    consumed,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::Ime(ime) => {
+                self.on_ime(ime);
+
+                EventResponse {
+                    repaint: true,
+                    consumed: self.egui_ctx.wants_keyboard_input(),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::KeyboardInput { input, .. } => {
+                let captured = self.on_keyboard_input(input);
+                // When pressing the Tab key, egui focuses the first focusable element, hence Tab always consumes.
+                let consumed = captured
+                    || self.egui_ctx.wants_keyboard_input()
+                    || input.virtual_keycode == Some(winit::event::VirtualKeyCode::Tab);
+                EventResponse {
+                    repaint: true,
+                    consumed,
+                    consume_reason: captured.then_some(ConsumeReason::KeyboardCapture),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::Focused(focused) => {
+                self.on_focused(Some(window), *focused);
+                EventResponse {
+                    repaint: true,
+                    focus_changed: Some(*focused),
+                    ..Default::default()
+                }
+            }
+            WindowEvent::HoveredFile(path) => {
+                self.egui_input.hovered_files.push(egui::HoveredFile {
+                    path: Some(path.clone()),
+                    ..Default::default()
+                });
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::HoveredFileCancelled => {
+                self.egui_input.hovered_files.clear();
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.on_dropped_file(path);
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::ModifiersChanged(state) => {
+                self.on_modifiers_changed(*state);
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+
+            WindowEvent::Occluded(occluded) => {
+                self.on_occluded(*occluded);
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
+            WindowEvent::Resized(_) => EventResponse {
+                repaint: self.should_repaint_on_resize(),
+                ..Default::default()
             },
-            |m| m.scale_factor() as f32,
-        );
-    let zoom_factor = egui_ctx.zoom_factor();
-    let pixels_per_point = zoom_factor * native_pixels_per_point;
 
-    let ViewportBuilder {
-        title,
-        position,
-        inner_size,
-        min_inner_size,
-        max_inner_size,
-        fullscreen,
-        maximized,
-        resizable,
-        transparent,
-        decorations,
-        icon,
-        active,
-        visible,
-        close_button,
-        minimize_button,
-        maximize_button,
-        window_level,
+            WindowEvent::CursorEntered { .. } => {
+                self.left_during_drag = false;
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
 
-        // macOS:
-        fullsize_content_view: _fullsize_content_view,
-        title_shown: _title_shown,
-        titlebar_buttons_shown: _titlebar_buttons_shown,
-        titlebar_shown: _titlebar_shown,
+            WindowEvent::ThemeChanged(theme) => {
+                self.on_theme_changed(*theme);
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
 
-        // Windows:
-        drag_and_drop: _drag_and_drop,
+            // Things that may require repaint:
+            WindowEvent::Destroyed | WindowEvent::Moved(_) | WindowEvent::CloseRequested => {
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
 
-        // wayland:
-        app_id: _app_id,
+            WindowEvent::AxisMotion { axis, value, .. } => {
+                let moved = self.on_axis_motion(*axis, *value);
+                EventResponse {
+                    repaint: moved,
+                    consumed: moved && self.egui_ctx.is_using_pointer(),
+                    ..Default::default()
+                }
+            }
 
-        mouse_passthrough: _, // handled in `apply_viewport_builder_to_window`
-    } = viewport_builder;
+            WindowEvent::TouchpadRotate { delta, .. } => {
+                self.on_touchpad_rotate(*delta);
+                EventResponse {
+                    repaint: true,
+                    ..Default::default()
+                }
+            }
 
-    let mut window_builder = winit::window::WindowBuilder::new()
-        .with_title(title.unwrap_or_else(|| "egui window".to_owned()))
-        .with_transparent(transparent.unwrap_or(false))
-        .with_decorations(decorations.unwrap_or(true))
-        .with_resizable(resizable.unwrap_or(true))
-        .with_visible(visible.unwrap_or(true))
-        .with_maximized(maximized.unwrap_or(false))
-        .with_window_level(match window_level.unwrap_or_default() {
-            egui::viewport::WindowLevel::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
-            egui::viewport::WindowLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
-            egui::viewport::WindowLevel::Normal => WindowLevel::Normal,
-        })
-        .with_fullscreen(
-            fullscreen.and_then(|e| e.then_some(winit::window::Fullscreen::Borderless(None))),
-        )
-        .with_enabled_buttons({
-            let mut buttons = WindowButtons::empty();
-            if minimize_button.unwrap_or(true) {
-                buttons |= WindowButtons::MINIMIZE;
+            WindowEvent::SmartMagnify { .. } => {
+                let handled = self.on_smart_magnify();
+                EventResponse {
+                    repaint: handled,
+                    consumed: handled && self.egui_ctx.wants_pointer_input(),
+                    ..Default::default()
+                }
             }
-            if maximize_button.unwrap_or(true) {
-                buttons |= WindowButtons::MAXIMIZE;
+
+            WindowEvent::TouchpadMagnify { delta, .. } => {
+                self.on_touchpad_magnify(*delta);
+                EventResponse {
+                    repaint: true,
+                    consumed: self.egui_ctx.wants_pointer_input(),
+                    ..Default::default()
+                }
             }
-            if close_button.unwrap_or(true) {
-                buttons |= WindowButtons::CLOSE;
+        }
+    }
+
+    /// Call this when there is a new event from a source other than winit,
+    /// e.g. input streamed from a remote client.
+    ///
+    /// This goes through the same translation as [`Self::on_window_event`].
+    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
+    pub fn on_remote_event(&mut self, event: RemoteEvent) -> EventResponse {
+        crate::profile_function!();
+
+        if !matches!(event, RemoteEvent::Focused(_)) {
+            self.last_input_instant = Some(web_time::Instant::now());
+        }
+
+        let focus_changed = match event {
+            RemoteEvent::Focused(focused) => Some(focused),
+            _ => None,
+        };
+
+        let consume_reason = match event {
+            RemoteEvent::PointerMoved(pos) => {
+                self.on_pointer_moved(pos);
+                self.egui_ctx
+                    .is_using_pointer()
+                    .then_some(ConsumeReason::PointerInUse)
             }
-            buttons
-        })
-        .with_active(active.unwrap_or(true));
+            RemoteEvent::PointerButton { button, pressed } => {
+                self.on_pointer_button(button, pressed);
+                self.egui_ctx
+                    .wants_pointer_input()
+                    .then(|| self.pointer_consume_reason())
+            }
+            RemoteEvent::PointerGone => {
+                self.pointer_pos_in_points = None;
+                self.egui_input.events.push(egui::Event::PointerGone);
+                None
+            }
+            RemoteEvent::Key { key, pressed } => {
+                self.on_key(key, pressed);
+                if self.egui_ctx.wants_keyboard_input() {
+                    Some(ConsumeReason::KeyboardFocus)
+                } else {
+                    (key == egui::Key::Tab).then_some(ConsumeReason::TabTraversal)
+                }
+            }
+            RemoteEvent::Text(text) => {
+                let text: String = text.chars().filter(|&ch| is_printable_char(ch)).collect();
+                if text.is_empty() {
+                    None
+                } else {
+                    self.egui_input.events.push(egui::Event::Text(text));
+                    self.egui_ctx
+                        .wants_keyboard_input()
+                        .then_some(ConsumeReason::KeyboardFocus)
+                }
+            }
+            RemoteEvent::Scroll { unit, delta } => {
+                self.on_scroll(unit, delta);
+                self.egui_ctx
+                    .wants_pointer_input()
+                    .then(|| self.pointer_consume_reason())
+            }
+            RemoteEvent::Modifiers(modifiers) => {
+                self.egui_input.modifiers = modifiers;
+                None
+            }
+            RemoteEvent::Focused(focused) => {
+                self.set_focused(focused);
+                None
+            }
+        };
+
+        EventResponse {
+            repaint: !self.trust_egui_repaint,
+            consumed: consume_reason.is_some(),
+            consume_reason,
+            focus_changed,
+        }
+    }
+
+    /// Call this when there is a new [`winit::event::DeviceEvent`].
+    ///
+    /// Only [`winit::event::DeviceEvent::MouseMotion`] is used: it is accumulated
+    /// and can be read with [`Self::take_raw_mouse_delta`].
+    /// This is useful when the cursor is grabbed, and no `CursorMoved` events arrive.
+    ///
+    /// Device events arrive even when the window is not focused, and the delta never reaches egui,
+    /// so this never asks for a repaint. Repaint yourself if [`Self::take_raw_mouse_delta`] is non-zero.
+    pub fn on_device_event(&mut self, event: &winit::event::DeviceEvent) -> EventResponse {
+        if let winit::event::DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            self.raw_mouse_delta += egui::vec2(*dx as f32, *dy as f32);
+        }
+        EventResponse::default()
+    }
+
+    /// Call this when there is a new [`accesskit::ActionRequest`].
+    ///
+    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
+    #[cfg(feature = "accesskit")]
+    pub fn on_accesskit_action_request(&mut self, request: accesskit::ActionRequest) {
+        self.egui_input
+            .events
+            .push(egui::Event::AccessKitActionRequest(request));
+    }
+
+    /// Call this when the user picks an item from a native menu.
+    ///
+    /// The result can be found in [`Self::egui_input`] and be extracted with [`Self::take_egui_input`].
+    pub fn on_menu_action(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Copy => self.trigger_clipboard_action(ClipboardAction::Copy),
+            MenuAction::Cut => self.trigger_clipboard_action(ClipboardAction::Cut),
+            MenuAction::Paste => self.trigger_clipboard_action(ClipboardAction::Paste),
+            MenuAction::SelectAll
+            | MenuAction::Undo
+            | MenuAction::Redo
+            | MenuAction::Shortcut(_) => {
+                if let Some(egui::KeyboardShortcut { modifiers, key }) = action.shortcut() {
+                    for pressed in [true, false] {
+                        self.egui_input.events.push(egui::Event::Key {
+                            key,
+                            pressed,
+                            repeat: false,
+                            modifiers,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy, cut or paste in the focused egui widget, e.g. from an Edit menu or a toolbar button.
+    ///
+    /// For [`ClipboardAction::Paste`] the text is read from the clipboard right away.
+    pub fn trigger_clipboard_action(&mut self, action: ClipboardAction) {
+        match action {
+            ClipboardAction::Copy => self.egui_input.events.push(egui::Event::Copy),
+            ClipboardAction::Cut => self.egui_input.events.push(egui::Event::Cut),
+            ClipboardAction::Paste => self.paste_from_clipboard(),
+        }
+    }
+
+    fn paste_from_clipboard(&mut self) {
+        if let Some(contents) = self.clipboard.get().filter(|text| !text.is_empty()) {
+            self.paste(contents, PasteSource::Clipboard);
+        } else if let Some(image) = self.clipboard.get_image() {
+            self.egui_input
+                .events
+                .push(egui::Event::PasteImage(std::sync::Arc::new(image)));
+            self.last_paste_source = Some(PasteSource::Clipboard);
+        }
+    }
+
+    /// Put an image on the OS clipboard, e.g. to copy out something the app rendered.
+    ///
+    /// Pasting an image produces an [`egui::Event::PasteImage`].
+    pub fn copy_image(&mut self, image: &egui::ColorImage) {
+        self.clipboard.set_image(image);
+    }
+
+    /// Paste `text` into the focused egui widget, as if it came from the clipboard.
+    ///
+    /// [`Self::last_paste_source`] will be [`PasteSource::Injected`].
+    pub fn inject_paste(&mut self, text: String) {
+        self.paste(text, PasteSource::Injected);
+    }
+
+    fn paste(&mut self, contents: String, source: PasteSource) {
+        let contents = contents.replace("\r\n", "\n");
+        if !contents.is_empty() {
+            self.egui_input.events.push(egui::Event::Paste(contents));
+            self.last_paste_source = Some(source);
+        }
+    }
+
+    /// The phase of the last scroll gesture, e.g. for snapping a scroll area when the gesture ends.
+    ///
+    /// This is `None` when unknown, e.g. when scrolling with a notched mouse wheel.
+    /// Only some platforms (e.g. Mac trackpads) report when a scroll gesture starts and ends;
+    /// others always report [`egui::TouchPhase::Move`].
+    #[inline]
+    pub fn last_scroll_phase(&self) -> Option<egui::TouchPhase> {
+        self.last_scroll_phase
+    }
+
+    /// Where the last [`egui::Event::Paste`] or [`egui::Event::PasteImage`] came from,
+    /// or `None` if nothing has been pasted yet.
+    #[inline]
+    pub fn last_paste_source(&self) -> Option<PasteSource> {
+        self.last_paste_source
+    }
+
+    fn on_mouse_button_input(
+        &mut self,
+        state: winit::event::ElementState,
+        button: winit::event::MouseButton,
+    ) {
+        let button = match button {
+            winit::event::MouseButton::Other(id) => self.mouse_button_mapping.get(&id).copied(),
+            _ => None,
+        }
+        .or_else(|| translate_mouse_button(button));
+
+        if let Some(button) = button {
+            self.on_pointer_button(button, state == winit::event::ElementState::Pressed);
+        }
+    }
+
+    fn on_pointer_button(&mut self, button: egui::PointerButton, pressed: bool) {
+        if let Some(pos) = self.pointer_pos_in_points {
+            if self.single_pointer_button {
+                match self.held_pointer_button {
+                    None if pressed => self.held_pointer_button = Some(button),
+                    Some(held) if held == button && !pressed => {
+                        self.held_pointer_button = None;
+                    }
+                    _ => return,
+                }
+            }
+
+            self.egui_input.events.push(egui::Event::PointerButton {
+                pos,
+                button,
+                pressed,
+                modifiers: self.egui_input.modifiers,
+            });
+
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            if button == egui::PointerButton::Middle
+                && pressed
+                && self.egui_ctx.wants_pointer_input()
+                && self.egui_ctx.wants_keyboard_input()
+            {
+                // Middle-click pastes the primary selection, like in other Linux apps.
+                if let Some(contents) = self.clipboard.get_primary() {
+                    self.paste(contents, PasteSource::Primary);
+                }
+            }
+
+            match self.drag_start {
+                None if pressed => self.drag_start = Some((pos, button)),
+                Some((_, drag_button)) if drag_button == button && !pressed => {
+                    self.drag_start = None;
+                    if self.left_during_drag {
+                        self.left_during_drag = false;
+                        self.pointer_pos_in_points = None;
+                        self.egui_input.events.push(egui::Event::PointerGone);
+                    }
+                }
+                _ => {}
+            }
+
+            if self.simulates_touch() {
+                if pressed {
+                    self.any_pointer_button_down = true;
+
+                    self.egui_input.events.push(egui::Event::Touch {
+                        device_id: SIMULATED_TOUCH_DEVICE_ID,
+                        id: SIMULATED_TOUCH_ID,
+                        phase: egui::TouchPhase::Start,
+                        pos,
+                        force: None,
+                    });
+                } else {
+                    self.any_pointer_button_down = false;
+
+                    self.egui_input.events.push(egui::Event::PointerGone);
+
+                    self.egui_input.events.push(egui::Event::Touch {
+                        device_id: SIMULATED_TOUCH_DEVICE_ID,
+                        id: SIMULATED_TOUCH_ID,
+                        phase: egui::TouchPhase::End,
+                        pos,
+                        force: None,
+                    });
+                };
+            }
+        }
+    }
+
+    fn on_cursor_moved(
+        &mut self,
+        window: &Window,
+        pos_in_pixels: winit::dpi::PhysicalPosition<f64>,
+    ) {
+        let pixels_per_point = pixels_per_point(&self.egui_ctx, window);
+
+        let pos_in_points = pointer_pos_in_points(
+            pos_in_pixels,
+            pixels_per_point,
+            self.round_pointer_to_pixels,
+        );
+        self.on_pointer_moved(pos_in_points);
+    }
+
+    fn on_cursor_left(&mut self) {
+        if self.drag_sticky && self.drag_start.is_some() {
+            // Keep dragging until the button is released
+            self.left_during_drag = true;
+            return;
+        }
+        self.pointer_pos_in_points = None;
+        self.egui_input.events.push(egui::Event::PointerGone);
+    }
+
+    fn on_pointer_moved(&mut self, pos_in_points: egui::Pos2) {
+        self.pointer_pos_in_points = Some(pos_in_points);
+
+        if self.simulates_touch() {
+            if self.any_pointer_button_down {
+                self.egui_input
+                    .events
+                    .push(egui::Event::PointerMoved(pos_in_points));
+
+                self.egui_input.events.push(egui::Event::Touch {
+                    device_id: SIMULATED_TOUCH_DEVICE_ID,
+                    id: SIMULATED_TOUCH_ID,
+                    phase: egui::TouchPhase::Move,
+                    pos: pos_in_points,
+                    force: None,
+                });
+            }
+        } else {
+            self.egui_input
+                .events
+                .push(egui::Event::PointerMoved(pos_in_points));
+        }
+    }
+
+    fn should_repaint_on_resize(&mut self) -> bool {
+        let Some(throttle) = self.resize_repaint_throttle else {
+            return true;
+        };
+
+        let now = web_time::Instant::now();
+        match self.last_resize_repaint {
+            Some(last) if now.duration_since(last) < throttle => {
+                // Make sure we still paint the final size once the resizing stops:
+                let remaining = throttle - now.duration_since(last);
+                self.egui_ctx
+                    .request_repaint_after_for(remaining, self.viewport_id);
+                false
+            }
+            _ => {
+                self.last_resize_repaint = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if any request for the user's attention was reset.
+    fn on_focused(&mut self, window: Option<&Window>, focused: bool) -> bool {
+        self.set_focused(focused);
+        if !focused {
+            return false;
+        }
+
+        // The user has noticed us, so stop bouncing the dock icon / flashing the taskbar.
+        // The request may have come from a `ViewportCommand`, so we can't know if there is one.
+        if let Some(window) = window {
+            window.request_user_attention(None);
+        }
+        true
+    }
+
+    /// See [`Self::last_input_instant`].
+    fn on_user_input(&mut self, event: &winit::event::WindowEvent<'_>) {
+        if is_user_input_event(event) {
+            self.last_input_instant = Some(web_time::Instant::now());
+        }
+    }
+
+    /// See [`Self::set_clear_pointer_on_occlude`].
+    fn on_occluded(&mut self, occluded: bool) {
+        if occluded && self.clear_pointer_on_occlude {
+            self.pointer_pos_in_points = None;
+            self.egui_input.events.push(egui::Event::PointerGone);
+        }
+    }
+
+    /// See [`Self::set_focus_follows_mouse`].
+    fn on_hover_focus(&mut self) {
+        if self.focus_follows_mouse && !self.egui_input.focused {
+            self.set_focused(true);
+        }
+    }
+
+    /// Returns `true` if the pointer was moved.
+    fn on_axis_motion(&mut self, axis: u32, value: f64) -> bool {
+        let Some(config) = self.axis_pointer else {
+            return false;
+        };
+
+        let delta = value as f32 * config.points_per_unit;
+        let delta = if axis == config.x_axis {
+            egui::vec2(delta, 0.0)
+        } else if axis == config.y_axis {
+            egui::vec2(0.0, delta)
+        } else {
+            return false;
+        };
+
+        let pos_in_points = self.pointer_pos_in_points.unwrap_or(Pos2::ZERO) + delta;
+        self.pointer_pos_in_points = Some(pos_in_points);
+        self.egui_input
+            .events
+            .push(egui::Event::PointerMoved(pos_in_points));
+        true
+    }
+
+    /// Returns `true` if force touch is enabled.
+    fn on_touchpad_pressure(
+        &mut self,
+        device_id: winit::event::DeviceId,
+        pressure: f32,
+        stage: i64,
+    ) -> bool {
+        self.pending_touchpad_pressure = Some((pressure, stage));
+
+        if self.force_touch.is_none() {
+            return false;
+        }
+
+        let prev_stage = std::mem::replace(&mut self.touchpad_pressure_stage, stage);
+
+        if let Some(pos) = self.pointer_pos_in_points {
+            let phase = if prev_stage == 0 && 0 < stage {
+                Some(egui::TouchPhase::Start)
+            } else if 0 < prev_stage && stage == 0 {
+                Some(egui::TouchPhase::End)
+            } else if 0 < stage {
+                Some(egui::TouchPhase::Move)
+            } else {
+                None
+            };
+
+            if let Some(phase) = phase {
+                self.egui_input.events.push(egui::Event::Touch {
+                    device_id: translate_touch_device_id(device_id),
+                    id: FORCE_TOUCH_ID,
+                    phase,
+                    pos,
+                    force: Some(pressure),
+                });
+            }
+        }
+
+        if is_force_click(prev_stage, stage) {
+            match self.force_touch.clone() {
+                Some(ForceClickAction::SecondaryClick) => {
+                    if let Some(pos) = self.pointer_pos_in_points {
+                        for pressed in [true, false] {
+                            self.egui_input.events.push(egui::Event::PointerButton {
+                                pos,
+                                button: egui::PointerButton::Secondary,
+                                pressed,
+                                modifiers: self.egui_input.modifiers,
+                            });
+                        }
+                    }
+                }
+                Some(ForceClickAction::Event(event)) => {
+                    self.egui_input.events.push(event);
+                }
+                None => {}
+            }
+        }
+
+        true
+    }
+
+    /// The normalized force of a touch, with the force curve applied.
+    fn touch_force(&self, force: Option<winit::event::Force>) -> Option<f32> {
+        let force = match force? {
+            winit::event::Force::Normalized(force) => force as f32,
+            winit::event::Force::Calibrated {
+                force,
+                max_possible_force,
+                ..
+            } => (force / max_possible_force) as f32,
+        };
+        Some(match &self.force_curve {
+            Some(force_curve) => force_curve(force),
+            None => force,
+        })
+    }
+
+    fn on_touch(&mut self, touch: &winit::event::Touch) {
+        let was_simulating = self.simulates_touch();
+        self.seen_real_touch = true;
+        if was_simulating && !self.simulates_touch() {
+            self.cancel_simulated_touch();
+        }
+
+        let pixels_per_point = self.pixels_per_point;
+        let pos = egui::pos2(
+            touch.location.x as f32 / pixels_per_point,
+            touch.location.y as f32 / pixels_per_point,
+        );
+
+        // Emit touch event
+        self.egui_input.events.push(egui::Event::Touch {
+            device_id: translate_touch_device_id(touch.device_id),
+            id: egui::TouchId::from(touch.id),
+            phase: translate_touch_phase(touch.phase),
+            pos,
+            force: self.touch_force(touch.force),
+        });
+        self.on_pinch_touch(touch.id, touch.phase, pos);
+
+        // If we're not yet translating a touch or we're translating this very
+        // touch …
+        if self.pointer_touch_id.is_none() || self.pointer_touch_id.unwrap() == touch.id {
+            // … emit PointerButton resp. PointerMoved events to emulate mouse
+            match touch.phase {
+                winit::event::TouchPhase::Started => {
+                    self.pointer_touch_id = Some(touch.id);
+                    // First move the pointer to the right location
+                    self.on_pointer_moved(pos);
+                    self.on_mouse_button_input(
+                        winit::event::ElementState::Pressed,
+                        winit::event::MouseButton::Left,
+                    );
+                }
+                winit::event::TouchPhase::Moved => {
+                    self.on_pointer_moved(pos);
+                }
+                winit::event::TouchPhase::Ended => {
+                    self.pointer_touch_id = None;
+                    self.on_mouse_button_input(
+                        winit::event::ElementState::Released,
+                        winit::event::MouseButton::Left,
+                    );
+                    self.on_pointer_touch_lifted();
+                }
+                winit::event::TouchPhase::Cancelled => {
+                    self.pointer_touch_id = None;
+                    self.on_pointer_touch_lifted();
+                }
+            }
+        }
+    }
+
+    /// The touch that was emulating the pointer is gone.
+    fn on_pointer_touch_lifted(&mut self) {
+        if let Some(&(id, pos)) = self.pinch_touches.first() {
+            // A finger lifted mid-pinch: the other one takes over the (released) pointer,
+            // so that it doesn't pop up out of nowhere on its next move.
+            self.pointer_touch_id = Some(id);
+            self.on_pointer_moved(pos);
+        } else {
+            // The pointer should vanish completely to not get any
+            // hover effects
+            self.pointer_pos_in_points = None;
+            self.egui_input.events.push(egui::Event::PointerGone);
+        }
+    }
+
+    /// Track the first two touches, and turn changes in the distance between them into [`egui::Event::Zoom`].
+    fn on_pinch_touch(&mut self, id: u64, phase: winit::event::TouchPhase, pos: egui::Pos2) {
+        match phase {
+            winit::event::TouchPhase::Started => {
+                if self.pinch_touches.len() < 2 {
+                    self.pinch_touches.push((id, pos));
+                }
+            }
+            winit::event::TouchPhase::Moved => {
+                let old_distance = self.pinch_distance();
+                if let Some((_, last_pos)) = self.pinch_touches.iter_mut().find(|(t, _)| *t == id) {
+                    *last_pos = pos;
+                }
+                if let (Some(old_distance), Some(new_distance)) =
+                    (old_distance, self.pinch_distance())
+                {
+                    if self.allow_gui_zoom && 0.0 < old_distance && 0.0 < new_distance {
+                        self.egui_input
+                            .events
+                            .push(egui::Event::Zoom(new_distance / old_distance));
+                    }
+                }
+            }
+            winit::event::TouchPhase::Ended | winit::event::TouchPhase::Cancelled => {
+                self.pinch_touches.retain(|(t, _)| *t != id);
+            }
+        }
+    }
+
+    /// The distance between the two fingers of an ongoing pinch, if any.
+    fn pinch_distance(&self) -> Option<f32> {
+        match self.pinch_touches.as_slice() {
+            [(_, a), (_, b)] => Some(a.distance(*b)),
+            _ => None,
+        }
+    }
+
+    fn on_smart_magnify(&mut self) -> bool {
+        if let Some(event) = self.smart_magnify_event.clone() {
+            self.egui_input.events.push(event);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `delta_degrees` is counter-clockwise.
+    fn on_touchpad_rotate(&mut self, delta_degrees: f32) {
+        self.pending_rotation_delta += delta_degrees.to_radians();
+    }
+
+    /// Start a new frame of [`Self::take_rotation_delta`] and [`Self::take_touchpad_pressure`].
+    fn latch_trackpad_gestures(&mut self) {
+        self.rotation_delta = std::mem::take(&mut self.pending_rotation_delta);
+        self.touchpad_pressure = self.pending_touchpad_pressure.take();
+    }
+
+    fn on_touchpad_magnify(&mut self, delta: f64) {
+        if !self.allow_gui_zoom {
+            return;
+        }
+
+        // `egui::Event::Zoom` has no focal point, so make sure egui knows where the pinch is,
+        // so that it zooms about it. winit doesn't tell us the centroid, but it is under the cursor.
+        if let Some(pos) = self.pointer_pos_in_points {
+            self.egui_input.events.push(egui::Event::PointerMoved(pos));
+        }
+
+        // Positive delta values indicate magnification (zooming in).
+        // Negative delta values indicate shrinking (zooming out).
+        let zoom_factor = (delta as f32).exp();
+        self.egui_input.events.push(egui::Event::Zoom(zoom_factor));
+    }
+
+    fn on_mouse_wheel(&mut self, delta: winit::event::MouseScrollDelta) {
+        let pixels_per_point = self.pixels_per_point;
+
+        let (unit, delta) = match delta {
+            winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                (egui::MouseWheelUnit::Line, egui::vec2(x, y))
+            }
+            winit::event::MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition { x, y }) => (
+                egui::MouseWheelUnit::Point,
+                egui::vec2(x as f32, y as f32) / pixels_per_point,
+            ),
+        };
+        self.on_scroll(unit, delta);
+    }
+
+    /// `delta` is in lines, points or pages, depending on `unit`.
+    fn on_scroll(&mut self, unit: egui::MouseWheelUnit, delta: egui::Vec2) {
+        let modifiers = self.egui_input.modifiers;
+        self.egui_input.events.push(egui::Event::MouseWheel {
+            unit,
+            delta,
+            modifiers,
+        });
+
+        let is_pixel_delta = unit == egui::MouseWheelUnit::Point;
+        let delta = match unit {
+            egui::MouseWheelUnit::Line => delta * self.points_per_scroll_line,
+            egui::MouseWheelUnit::Page => delta * self.egui_ctx.screen_rect().height(),
+            egui::MouseWheelUnit::Point => delta,
+        };
+
+        let is_horizontal_scroll = self
+            .horizontal_scroll_modifier
+            .is_some_and(|modifier| self.egui_input.modifiers.contains(modifier));
+
+        if is_horizontal_scroll {
+            // Treat as horizontal scrolling, unless the device already scrolls horizontally
+            // (e.g. a trackpad, or a Mac, which turns shift+scroll horizontal itself).
+            // Adding `delta.y` to a genuine `delta.x` would count the gesture twice.
+            let delta = if delta.x == 0.0 {
+                egui::vec2(delta.y, 0.0)
+            } else {
+                delta
+            };
+            self.egui_input.events.push(egui::Event::Scroll(delta));
+        } else if self.allow_gui_zoom
+            && self.zoom_with_ctrl_scroll
+            && (self.egui_input.modifiers.ctrl || self.egui_input.modifiers.command)
+        {
+            // Treat as zoom instead:
+            let factor = scroll_zoom_factor(
+                delta.y,
+                is_pixel_delta,
+                self.points_per_scroll_line,
+                self.ctrl_scroll_zoom_divisor,
+            );
+            self.egui_input.events.push(egui::Event::Zoom(factor));
+        } else {
+            self.egui_input.events.push(egui::Event::Scroll(delta));
+        }
+    }
+
+    /// Also send the text committed by an IME as an [`egui::Event::Text`],
+    /// right after the [`egui::Event::CompositionEnd`].
+    ///
+    /// Use this if you have widgets that only handle [`egui::Event::Text`].
+    /// Note that egui's own [`egui::TextEdit`] handles both, so it would insert the text twice.
+    ///
+    /// Default: `false`.
+    pub fn set_ime_commit_as_text(&mut self, ime_commit_as_text: bool) {
+        self.ime_commit_as_text = ime_commit_as_text;
+    }
+
+    /// Don't send the key presses of a `Compose` sequence (e.g. `Compose`, `'`, `e`) to egui,
+    /// only the composed character (`é`) as an [`egui::Event::Text`].
+    ///
+    /// Without this, egui may see both a [`egui::Event::Key`] and the composed text.
+    ///
+    /// Only sequences started with the `Compose` key are detected.
+    /// winit does not report dead keys, such as `'` and `^` on the US-International layout,
+    /// so those still reach egui as key presses.
+    /// A sequence ends with the composed character, or when a key that doesn't type text
+    /// (an arrow key, Enter, Escape, …) is pressed, since no character will arrive then.
+    ///
+    /// Default: `true`, except on Mac, where dead keys are handled by the IME instead.
+    pub fn set_suppress_dead_keys(&mut self, suppress_dead_keys: bool) {
+        self.suppress_dead_keys = suppress_dead_keys;
+        if !suppress_dead_keys {
+            self.dead_key_composing = false;
+            self.dead_key_suppressed.clear();
+        }
+    }
+
+    /// Simulate the user composing and then committing `text` with an IME (Input Method Editor).
+    ///
+    /// This pushes the same [`egui::Event::CompositionStart`], [`egui::Event::CompositionUpdate`]
+    /// and [`egui::Event::CompositionEnd`] events as a real IME would, which is useful for testing.
+    pub fn simulate_ime(&mut self, text: &str) {
+        let cursor = Some((text.len(), text.len()));
+        self.on_ime(&winit::event::Ime::Preedit(text.to_owned(), cursor));
+        self.on_ime(&winit::event::Ime::Commit(text.to_owned()));
+    }
+
+    fn on_dropped_file(&mut self, path: &std::path::Path) {
+        // All files dropped before the next `take_egui_input` form one batch.
+        let is_new_batch = self.egui_input.dropped_files.is_empty();
+        if is_new_batch {
+            self.last_drop_count = 0;
+        }
+        self.last_drop_count += 1;
+
+        // Only forget about this file being hovered, since some platforms interleave
+        // `HoveredFile` and `DroppedFile` events when several files are dropped.
+        self.egui_input
+            .hovered_files
+            .retain(|hovered| hovered.path.as_deref() != Some(path));
+
+        self.egui_input.dropped_files.push(egui::DroppedFile {
+            path: Some(path.to_owned()),
+            ..Default::default()
+        });
+    }
+
+    fn on_ime(&mut self, ime: &winit::event::Ime) {
+        // on Mac even Cmd-C is pressed during ime, a `c` is pushed to Preedit.
+        // So no need to check is_mac_cmd.
+        //
+        // How winit produce `Ime::Enabled` and `Ime::Disabled` differs in MacOS
+        // and Windows.
+        //
+        // - On Windows, before and after each Commit will produce an Enable/Disabled
+        // event.
+        // - On MacOS, only when user explicit enable/disable ime. No Disabled
+        // after Commit.
+        //
+        // We use input_method_editor_started to manually insert CompositionStart
+        // between Commits.
+        match ime {
+            winit::event::Ime::Enabled | winit::event::Ime::Disabled => (),
+            winit::event::Ime::Commit(text) => {
+                if self.input_method_editor_started {
+                    // Modifier releases may not reach us while the IME has focus
+                    // (e.g. on Windows), so we could be left with a stuck Shift.
+                    // Reset them, like we do on focus change.
+                    // The tradeoff: winit only sends `ModifiersChanged` when the modifiers change,
+                    // so a modifier that really is still held stays unset until it is pressed
+                    // or released again. A missed modifier is better than a stuck one.
+                    self.egui_input.modifiers = egui::Modifiers::default();
+                }
+                self.input_method_editor_started = false;
+                self.egui_input
+                    .events
+                    .push(egui::Event::CompositionEnd(text.clone()));
+                if self.ime_commit_as_text {
+                    self.egui_input.events.push(egui::Event::Text(text.clone()));
+                }
+            }
+            winit::event::Ime::Preedit(text, Some(_)) => {
+                if !self.input_method_editor_started {
+                    self.input_method_editor_started = true;
+                    self.egui_input.events.push(egui::Event::CompositionStart);
+                }
+                self.egui_input
+                    .events
+                    .push(egui::Event::CompositionUpdate(text.clone()));
+            }
+            winit::event::Ime::Preedit(_, None) => {}
+        }
+    }
+
+    fn on_modifiers_changed(&mut self, state: winit::event::ModifiersState) {
+        self.egui_input.modifiers = translate_modifiers(state, self.text_nav_style);
+    }
+
+    /// Returns `true` if a character was sent to egui.
+    fn on_received_character(&mut self, ch: char) -> bool {
+        // The dead-key sequence (if any) is complete:
+        self.dead_key_composing = false;
+
+        // On Mac we get here when the user presses Cmd-C (copy), ctrl-W, etc.
+        // We need to ignore these characters that are side-effects of commands.
+        let is_mac_cmd = cfg!(target_os = "macos")
+            && (self.egui_input.modifiers.ctrl || self.egui_input.modifiers.mac_cmd);
+
+        if is_printable_char(ch) && !is_mac_cmd {
+            self.egui_input
+                .events
+                .push(egui::Event::Text(ch.to_string()));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if this key event is part of a dead-key sequence,
+    /// and should not be sent to egui.
+    fn on_dead_key(&mut self, keycode: winit::event::VirtualKeyCode, pressed: bool) -> bool {
+        use winit::event::VirtualKeyCode;
+
+        if !self.suppress_dead_keys {
+            return false;
+        }
+
+        if keycode == VirtualKeyCode::Compose {
+            if pressed {
+                self.dead_key_composing = true;
+            }
+            return true;
+        }
+
+        let key = translate_virtual_key_code(keycode);
+        if pressed && self.dead_key_composing {
+            if keycode == VirtualKeyCode::Escape {
+                // The sequence was cancelled, so no character will arrive.
+                self.dead_key_composing = false;
+            } else if key.is_some_and(|key| !is_text_key(key)) {
+                // The sequence was aborted, and the key is meant for egui.
+                self.dead_key_composing = false;
+                return false;
+            }
+            if let Some(key) = key {
+                self.dead_key_suppressed.insert(key);
+            }
+            true
+        } else if !pressed {
+            key.is_some_and(|key| self.dead_key_suppressed.remove(&key))
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the input was consumed by the [`Self::set_keyboard_capture`] callback.
+    fn on_keyboard_input(&mut self, input: &winit::event::KeyboardInput) -> bool {
+        if let Some(capture) = &mut self.keyboard_capture {
+            if capture(input) {
+                return true;
+            }
+        }
+
+        if let Some(keycode) = input.virtual_keycode {
+            let pressed = input.state == winit::event::ElementState::Pressed;
+
+            if self.on_dead_key(keycode, pressed) {
+                return false;
+            }
+
+            let modifiers = self.egui_input.modifiers;
+            let mut undo_redo = None;
+
+            if pressed {
+                // VirtualKeyCode::Paste etc in winit are broken/untrustworthy,
+                // so we detect these things manually:
+                if is_cut_command(modifiers, keycode) {
+                    self.egui_input.events.push(egui::Event::Cut);
+                } else if is_copy_command(modifiers, keycode) {
+                    self.egui_input.events.push(egui::Event::Copy);
+                } else if is_paste_command(modifiers, keycode) {
+                    self.paste_from_clipboard();
+                } else if is_undo_command(self.text_nav_style, modifiers, keycode) {
+                    undo_redo = MenuAction::Undo.shortcut();
+                } else if is_redo_command(self.text_nav_style, modifiers, keycode) {
+                    undo_redo = MenuAction::Redo.shortcut();
+                }
+            }
+
+            if let Some(key) = translate_virtual_key_code(keycode) {
+                match undo_redo {
+                    // egui already understands the Z and Y shortcuts.
+                    // For others (e.g. alt+backspace) send the shortcut egui recognizes instead,
+                    // as a complete press and release, so that no key is left held down in egui:
+                    Some(shortcut) if !matches!(key, egui::Key::Z | egui::Key::Y) => {
+                        for pressed in [true, false] {
+                            self.egui_input.events.push(egui::Event::Key {
+                                key: shortcut.key,
+                                pressed,
+                                repeat: false,
+                                modifiers: shortcut.modifiers,
+                            });
+                        }
+                    }
+                    _ => self.on_key(key, pressed),
+                }
+            } else if let Some(sink) = &self.raw_keycode_sink {
+                sink(keycode, pressed);
+            }
+        }
+
+        false
+    }
+
+    fn on_key(&mut self, key: egui::Key, pressed: bool) {
+        let repeat = pressed && self.pressed_keys.contains(&key);
+
+        if pressed {
+            self.pressed_keys.insert(key);
+        } else {
+            self.pressed_keys.remove(&key);
+        }
+
+        self.egui_input.events.push(egui::Event::Key {
+            key,
+            pressed,
+            repeat: false, // egui will fill this in for us!
+            modifiers: self.egui_input.modifiers,
+        });
+
+        if self.key_repeat_acceleration.is_some() {
+            self.accelerate_key_repeat(key, pressed, repeat, web_time::Instant::now());
+        }
+    }
+
+    fn accelerate_key_repeat(
+        &mut self,
+        key: egui::Key,
+        pressed: bool,
+        repeat: bool,
+        now: web_time::Instant,
+    ) {
+        let Some(curve) = self.key_repeat_acceleration else {
+            return;
+        };
+
+        let direction = match key {
+            egui::Key::ArrowUp | egui::Key::PageUp => egui::vec2(0.0, 1.0),
+            egui::Key::ArrowDown | egui::Key::PageDown => egui::vec2(0.0, -1.0),
+            egui::Key::ArrowLeft => egui::vec2(1.0, 0.0),
+            egui::Key::ArrowRight => egui::vec2(-1.0, 0.0),
+            _ => return,
+        };
+
+        if !pressed {
+            if self.key_hold_start.is_some_and(|(held, _)| held == key) {
+                self.key_hold_start = None;
+            }
+        } else if !repeat {
+            self.key_hold_start = Some((key, now));
+        } else if let Some((held, start)) = self.key_hold_start {
+            // Arrow keys move the cursor in a focused text field, so don't scroll as well:
+            if held == key && !self.egui_ctx.wants_keyboard_input() {
+                let points = curve.points_per_repeat(now.duration_since(start));
+                if points > 0.0 {
+                    self.egui_input
+                        .events
+                        .push(egui::Event::Scroll(points * direction));
+                }
+            }
+        }
+    }
+
+    /// Call with the output given by `egui`.
+    ///
+    /// This will, if needed:
+    /// * update the cursor
+    /// * copy text to the clipboard
+    /// * open any clicked urls
+    /// * update the IME
+    /// *
+    ///
+    /// Returns a summary of what was done.
+    pub fn handle_platform_output(
+        &mut self,
+        window: &Window,
+        platform_output: egui::PlatformOutput,
+    ) -> PlatformOutputActions {
+        crate::profile_function!();
+        self.apply_platform_output(Some(window), platform_output)
+    }
+
+    /// [`Self::handle_platform_output`], leaving out the calls to the window if there is none.
+    fn apply_platform_output(
+        &mut self,
+        window: Option<&Window>,
+        platform_output: egui::PlatformOutput,
+    ) -> PlatformOutputActions {
+        let egui::PlatformOutput {
+            cursor_icon,
+            open_url,
+            copied_text,
+            events: _,                    // handled elsewhere
+            mutable_text_under_cursor: _, // only used in eframe web
+            text_cursor_pos,
+            #[cfg(feature = "accesskit")]
+            accesskit_update,
+        } = platform_output;
+
+        let PlatformOutputOptions {
+            apply_cursor,
+            apply_clipboard,
+            apply_open_url,
+            apply_ime,
+        } = self.platform_output_options;
+
+        let mut actions = PlatformOutputActions::default();
+
+        if apply_cursor {
+            actions.cursor_changed = self.set_cursor_icon(window, cursor_icon);
+        }
+
+        if let Some(open_url) = open_url.filter(|_| apply_open_url) {
+            actions.opened_url = self.open_url(open_url);
+        }
+
+        if apply_clipboard {
+            actions.copied = self.copy_text(copied_text);
+        }
+
+        if apply_ime {
+            let allow_ime = text_cursor_pos.is_some();
+            if self.allow_ime != allow_ime {
+                self.allow_ime = allow_ime;
+                if let Some(window) = window {
+                    window.set_ime_allowed(allow_ime);
+                }
+            }
+
+            if let Some(egui::Pos2 { x, y }) = text_cursor_pos {
+                if let Some(window) = window {
+                    window.set_ime_position(winit::dpi::LogicalPosition { x, y });
+                }
+                actions.ime_moved = true;
+            }
+        }
+
+        #[cfg(feature = "accesskit")]
+        if let Some(accesskit) = self.accesskit.as_ref() {
+            if let Some(update) = accesskit_update {
+                accesskit.update_if_active(|| update);
+            }
+        }
+
+        actions
+    }
+
+    /// Returns `true` if any text was copied.
+    fn copy_text(&mut self, copied_text: String) -> bool {
+        if copied_text.is_empty() {
+            return false;
+        }
+        self.clipboard
+            .set(with_newline_style(copied_text, self.copy_newline_style));
+        true
+    }
+
+    /// Returns the url if it was opened right away.
+    fn open_url(&mut self, open_url: egui::output::OpenUrl) -> Option<String> {
+        if self.defer_url_open {
+            self.pending_url = Some(open_url);
+            None
+        } else {
+            if let Some(handler) = &self.open_url_handler {
+                handler(&open_url);
+            } else {
+                open_url_in_browser(&open_url);
+            }
+            Some(open_url.url)
+        }
+    }
+
+    /// Returns `true` if the cursor icon was changed.
+    fn set_cursor_icon(&mut self, window: Option<&Window>, cursor_icon: egui::CursorIcon) -> bool {
+        if !self.update_cursor_icon(cursor_icon) {
+            return false;
+        }
+
+        let Some(window) = window else {
+            return true;
+        };
+        if let Some(winit_cursor_icon) = translate_cursor(cursor_icon) {
+            window.set_cursor_visible(true);
+            window.set_cursor_icon(winit_cursor_icon);
+        } else {
+            window.set_cursor_visible(false);
+        }
+        true
+    }
+
+    /// Returns `true` if the cursor icon should be set on the window.
+    fn update_cursor_icon(&mut self, cursor_icon: egui::CursorIcon) -> bool {
+        if self.dedup_cursor_sets && self.current_cursor_icon == Some(cursor_icon) {
+            // Prevent flickering near frame boundary when Windows OS tries to control cursor icon for window resizing.
+            // On other platforms: just early-out to save CPU.
+            return false;
+        }
+
+        let is_pointer_in_window = self.pointer_pos_in_points.is_some();
+        if is_pointer_in_window {
+            if self.last_reported_cursor_icon != Some(cursor_icon) {
+                if let Some(callback) = &mut self.cursor_change_callback {
+                    callback(cursor_icon);
+                    self.last_reported_cursor_icon = Some(cursor_icon);
+                }
+            }
+            self.current_cursor_icon = Some(cursor_icon);
+            true
+        } else {
+            // Remember to set the cursor again once the cursor returns to the screen:
+            self.current_cursor_icon = None;
+            false
+        }
+    }
+}
+
+/// The user's system-wide appearance and accessibility preferences.
+///
+/// Each field is `None` if it is unknown on this platform.
+///
+/// See [`system_preferences`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemPreferences {
+    /// Does the user prefer fewer animations?
+    ///
+    /// If so, consider setting [`egui::Style::animation_time`] to zero.
+    pub reduced_motion: Option<bool>,
+
+    /// Does the user prefer high-contrast colors?
+    pub high_contrast: Option<bool>,
+
+    /// Does the user prefer a light or a dark color scheme?
+    ///
+    /// This is the same as [`State::system_theme`], but read straight from a window.
+    pub theme: Option<egui::SystemTheme>,
+}
+
+/// Read the user's system-wide preferences, where the platform exposes them.
+///
+/// winit does not yet expose the reduced-motion and high-contrast settings,
+/// so those are always `None` for now.
+pub fn system_preferences(window: &Window) -> SystemPreferences {
+    SystemPreferences {
+        reduced_motion: None,
+        high_contrast: None,
+        theme: window.theme().map(translate_theme),
+    }
+}
+
+fn translate_theme(theme: winit::window::Theme) -> egui::SystemTheme {
+    match theme {
+        winit::window::Theme::Light => egui::SystemTheme::Light,
+        winit::window::Theme::Dark => egui::SystemTheme::Dark,
+    }
+}
+
+/// Update the given viewport info with the current state of the window.
+///
+/// Call before [`State::take_egui_input`].
+pub fn update_viewport_info(
+    viewport_info: &mut ViewportInfo,
+    egui_ctx: &egui::Context,
+    window: &Window,
+) {
+    crate::profile_function!();
+
+    let pixels_per_point = pixels_per_point(egui_ctx, window);
+
+    let has_a_position = match window.is_minimized() {
+        None | Some(true) => false,
+        Some(false) => true,
+    };
+
+    let inner_pos_px = if has_a_position {
+        window
+            .inner_position()
+            .map(|pos| Pos2::new(pos.x as f32, pos.y as f32))
+            .ok()
+    } else {
+        None
+    };
+
+    let outer_pos_px = if has_a_position {
+        window
+            .outer_position()
+            .map(|pos| Pos2::new(pos.x as f32, pos.y as f32))
+            .ok()
+    } else {
+        None
+    };
+
+    let inner_size_px = if has_a_position {
+        let size = window.inner_size();
+        Some(Vec2::new(size.width as f32, size.height as f32))
+    } else {
+        None
+    };
+
+    let outer_size_px = if has_a_position {
+        let size = window.outer_size();
+        Some(Vec2::new(size.width as f32, size.height as f32))
+    } else {
+        None
+    };
+
+    let inner_rect_px = if let (Some(pos), Some(size)) = (inner_pos_px, inner_size_px) {
+        Some(Rect::from_min_size(pos, size))
+    } else {
+        None
+    };
+
+    let outer_rect_px = if let (Some(pos), Some(size)) = (outer_pos_px, outer_size_px) {
+        Some(Rect::from_min_size(pos, size))
+    } else {
+        None
+    };
+
+    let inner_rect = inner_rect_px.map(|r| r / pixels_per_point);
+    let outer_rect = outer_rect_px.map(|r| r / pixels_per_point);
+
+    let monitor_size = {
+        crate::profile_scope!("monitor_size");
+        if let Some(monitor) = window.current_monitor() {
+            let size = monitor.size().to_logical::<f32>(pixels_per_point.into());
+            Some(egui::vec2(size.width, size.height))
+        } else {
+            None
+        }
+    };
+
+    viewport_info.focused = Some(window.has_focus());
+    viewport_info.fullscreen = Some(window.fullscreen().is_some());
+    viewport_info.inner_rect = inner_rect;
+    viewport_info.monitor_size = monitor_size;
+    viewport_info.native_pixels_per_point = Some(window.scale_factor() as f32);
+    viewport_info.outer_rect = outer_rect;
+    viewport_info.title = Some(window.title());
+
+    if false {
+        // It's tempting to do this, but it leads to a deadlock on Mac when running
+        // `cargo run -p custom_window_frame`.
+        // See https://github.com/emilk/egui/issues/3494
+        viewport_info.maximized = Some(window.is_maximized());
+        viewport_info.minimized = window.is_minimized().or(viewport_info.minimized);
+    }
+}
+
+/// Capture the current state of a window as a [`ViewportBuilder`],
+/// e.g. to open a copy of it, or to save it as the default for new windows.
+///
+/// This reads the title, position, size, fullscreen, decorations, resizability,
+/// visibility and enabled titlebar buttons.
+///
+/// winit can't tell us about the icon, transparency, window level or app id,
+/// so those are left unset.
+/// Maximized is left unset too, since querying it can deadlock on Mac
+/// (see <https://github.com/emilk/egui/issues/3494>).
+pub fn viewport_builder_from_window(egui_ctx: &egui::Context, window: &Window) -> ViewportBuilder {
+    crate::profile_function!();
+
+    let mut viewport_info = ViewportInfo::default();
+    update_viewport_info(&mut viewport_info, egui_ctx, window);
+
+    let buttons = window.enabled_buttons();
+    let mut builder = viewport_builder_from_info(&viewport_info)
+        .with_decorations(window.is_decorated())
+        .with_resizable(window.is_resizable())
+        .with_close_button(buttons.contains(winit::window::WindowButtons::CLOSE))
+        .with_minimize_button(buttons.contains(winit::window::WindowButtons::MINIMIZE))
+        .with_maximize_button(buttons.contains(winit::window::WindowButtons::MAXIMIZE));
+    if let Some(visible) = window.is_visible() {
+        builder = builder.with_visible(visible);
+    }
+    builder
+}
+
+/// The parts of a [`ViewportBuilder`] we can fill in from a [`ViewportInfo`].
+fn viewport_builder_from_info(viewport_info: &ViewportInfo) -> ViewportBuilder {
+    let mut builder = ViewportBuilder::default();
+    if let Some(title) = &viewport_info.title {
+        builder = builder.with_title(title.clone());
+    }
+    if let Some(inner_rect) = viewport_info.inner_rect {
+        builder = builder.with_inner_size(inner_rect.size());
+    }
+    if let Some(outer_rect) = viewport_info.outer_rect {
+        builder = builder.with_position(outer_rect.min);
+    }
+    if let Some(fullscreen) = viewport_info.fullscreen {
+        builder = builder.with_fullscreen(fullscreen);
+    }
+    if let Some(maximized) = viewport_info.maximized {
+        builder = builder.with_maximized(maximized);
+    }
+    builder
+}
+
+/// Open the url in the system web browser.
+///
+/// A native app has no browser tab of its own to navigate, so the browser decides
+/// whether to use a new tab or window, whatever `new_tab` says.
+/// Use [`State::set_open_url_handler`] to honor `new_tab` yourself.
+fn open_url_in_browser(_open_url: &egui::OpenUrl) {
+    #[cfg(feature = "webbrowser")]
+    if let Err(err) = webbrowser::open(&_open_url.url) {
+        log::warn!("Failed to open url: {}", err);
+    }
+
+    #[cfg(not(feature = "webbrowser"))]
+    {
+        log::warn!("Cannot open url - feature \"links\" not enabled.");
+    }
+}
+
+fn translate_modifiers(
+    state: winit::event::ModifiersState,
+    text_nav_style: TextNavStyle,
+) -> egui::Modifiers {
+    let (mac_cmd, command) = match text_nav_style {
+        TextNavStyle::Mac => (state.logo(), state.logo()),
+        TextNavStyle::Windows => (false, state.ctrl()),
+        TextNavStyle::Emacs => (false, state.logo()),
+    };
+    egui::Modifiers {
+        alt: state.alt(),
+        ctrl: state.ctrl(),
+        shift: state.shift(),
+        mac_cmd,
+        command,
+    }
+}
+
+/// Default scroll speed decided by consensus: <https://github.com/emilk/egui/issues/461>
+const POINTS_PER_SCROLL_LINE: f32 = 50.0;
+
+/// Default for [`State::set_ctrl_scroll_zoom_divisor`].
+const CTRL_SCROLL_ZOOM_DIVISOR: f32 = 200.0;
+
+/// How much to zoom when scrolling `delta_y` points with ctrl/cmd held.
+fn scroll_zoom_factor(
+    delta_y: f32,
+    is_pixel_delta: bool,
+    points_per_scroll_line: f32,
+    divisor: f32,
+) -> f32 {
+    // Precision touchpads can report huge pixel deltas in a single event,
+    // so limit those to one scroll line to avoid runaway zoom.
+    let delta_y = if is_pixel_delta {
+        delta_y.clamp(-points_per_scroll_line, points_per_scroll_line)
+    } else {
+        delta_y
+    };
+    (delta_y / divisor).exp()
+}
+
+fn translate_user_attention(
+    attention: egui::UserAttentionType,
+) -> Option<winit::window::UserAttentionType> {
+    match attention {
+        egui::UserAttentionType::Reset => None,
+        egui::UserAttentionType::Critical => Some(winit::window::UserAttentionType::Critical),
+        egui::UserAttentionType::Informational => {
+            Some(winit::window::UserAttentionType::Informational)
+        }
+    }
+}
+
+fn with_newline_style(text: String, newline_style: NewlineStyle) -> String {
+    let crlf = match newline_style {
+        NewlineStyle::Lf => false,
+        NewlineStyle::CrLf => true,
+        NewlineStyle::Platform => cfg!(windows),
+    };
+    if crlf {
+        text.replace("\r\n", "\n").replace('\n', "\r\n")
+    } else {
+        text
+    }
+}
+
+/// Move the mouse cursor to `pos` (in egui points), clamped to the inside of the window.
+fn warp_cursor(
+    window: &Window,
+    pixels_per_point: f32,
+    pos: egui::Pos2,
+) -> Result<(), winit::error::ExternalError> {
+    let pos_px = clamp_to_window_px(
+        pos.to_vec2() * pixels_per_point,
+        screen_size_in_pixels(window),
+    );
+    window.set_cursor_position(PhysicalPosition::new(pos_px.x, pos_px.y))
+}
+
+/// Clamp a position in physical pixels to a window of the given inner size.
+fn clamp_to_window_px(pos_px: egui::Vec2, window_size_px: egui::Vec2) -> egui::Vec2 {
+    let max = (window_size_px - egui::Vec2::splat(1.0)).max(egui::Vec2::ZERO);
+    pos_px.clamp(egui::Vec2::ZERO, max)
+}
+
+fn translate_touch_phase(phase: winit::event::TouchPhase) -> egui::TouchPhase {
+    match phase {
+        winit::event::TouchPhase::Started => egui::TouchPhase::Start,
+        winit::event::TouchPhase::Moved => egui::TouchPhase::Move,
+        winit::event::TouchPhase::Ended => egui::TouchPhase::End,
+        winit::event::TouchPhase::Cancelled => egui::TouchPhase::Cancel,
+    }
+}
+
+/// The phase of a scroll gesture, or `None` if unknown.
+fn scroll_phase(
+    delta: winit::event::MouseScrollDelta,
+    phase: winit::event::TouchPhase,
+) -> Option<egui::TouchPhase> {
+    match delta {
+        // A notched mouse wheel has no gesture, and winit just reports `Moved`.
+        winit::event::MouseScrollDelta::LineDelta(..) => None,
+        winit::event::MouseScrollDelta::PixelDelta(_) => Some(translate_touch_phase(phase)),
+    }
+}
+
+/// The touch id used for the pressure of a Force Touch trackpad.
+const FORCE_TOUCH_ID: egui::TouchId = egui::TouchId(u64::MAX);
+
+/// The device of the touches simulated from the mouse, see [`TouchMode`].
+///
+/// Real touch devices never get this id, see [`translate_touch_device_id`].
+const SIMULATED_TOUCH_DEVICE_ID: egui::TouchDeviceId = egui::TouchDeviceId(u64::MAX);
+
+/// The touch id of the touches simulated from the mouse, see [`TouchMode`].
+const SIMULATED_TOUCH_ID: egui::TouchId = egui::TouchId(u64::MAX - 1);
+
+/// The egui id of a real touch device, which is never [`SIMULATED_TOUCH_DEVICE_ID`].
+fn translate_touch_device_id(device_id: winit::event::DeviceId) -> egui::TouchDeviceId {
+    let hash = egui::epaint::util::hash(device_id);
+    if hash == SIMULATED_TOUCH_DEVICE_ID.0 {
+        egui::TouchDeviceId(hash - 1)
+    } else {
+        egui::TouchDeviceId(hash)
+    }
+}
+
+/// Did the trackpad pressure just go from a normal click (stage 1) to a force click (stage 2)?
+fn is_force_click(prev_stage: i64, stage: i64) -> bool {
+    prev_stage < 2 && 2 <= stage
+}
+
+/// Is this event the result of the user interacting with the window
+/// using the pointer, keyboard or touch?
+///
+/// Synthetic key events, which winit sends for keys held when the window gains or loses focus,
+/// are not.
+fn is_user_input_event(event: &winit::event::WindowEvent<'_>) -> bool {
+    use winit::event::WindowEvent;
+
+    matches!(
+        event,
+        WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::ReceivedCharacter(_)
+            | WindowEvent::Ime(_)
+            | WindowEvent::KeyboardInput {
+                is_synthetic: false,
+                ..
+            }
+            | WindowEvent::TouchpadMagnify { .. }
+            | WindowEvent::SmartMagnify { .. }
+            | WindowEvent::TouchpadRotate { .. }
+            | WindowEvent::TouchpadPressure { .. }
+            | WindowEvent::AxisMotion { .. }
+    )
+}
+
+/// Does this key type text (as opposed to e.g. the arrow keys or Enter)?
+fn is_text_key(key: egui::Key) -> bool {
+    use egui::Key;
+
+    matches!(
+        key,
+        Key::Space
+            | Key::Minus
+            | Key::PlusEquals
+            | Key::Num0
+            | Key::Num1
+            | Key::Num2
+            | Key::Num3
+            | Key::Num4
+            | Key::Num5
+            | Key::Num6
+            | Key::Num7
+            | Key::Num8
+            | Key::Num9
+            | Key::A
+            | Key::B
+            | Key::C
+            | Key::D
+            | Key::E
+            | Key::F
+            | Key::G
+            | Key::H
+            | Key::I
+            | Key::J
+            | Key::K
+            | Key::L
+            | Key::M
+            | Key::N
+            | Key::O
+            | Key::P
+            | Key::Q
+            | Key::R
+            | Key::S
+            | Key::T
+            | Key::U
+            | Key::V
+            | Key::W
+            | Key::X
+            | Key::Y
+            | Key::Z
+    )
+}
+
+/// Winit sends special keys (backspace, delete, F1, …) as characters.
+/// Ignore those.
+/// We also ignore '\r', '\n', '\t'.
+/// Newlines are handled by the `Key::Enter` event.
+fn is_printable_char(chr: char) -> bool {
+    let is_in_private_use_area = '\u{e000}' <= chr && chr <= '\u{f8ff}'
+        || '\u{f0000}' <= chr && chr <= '\u{ffffd}'
+        || '\u{100000}' <= chr && chr <= '\u{10fffd}';
+
+    !is_in_private_use_area && !chr.is_ascii_control()
+}
+
+fn is_cut_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
+    (modifiers.command && keycode == winit::event::VirtualKeyCode::X)
+        || (cfg!(target_os = "windows")
+            && modifiers.shift
+            && keycode == winit::event::VirtualKeyCode::Delete)
+}
+
+fn is_copy_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
+    (modifiers.command && keycode == winit::event::VirtualKeyCode::C)
+        || (cfg!(target_os = "windows")
+            && modifiers.ctrl
+            && keycode == winit::event::VirtualKeyCode::Insert)
+}
+
+fn is_paste_command(modifiers: egui::Modifiers, keycode: winit::event::VirtualKeyCode) -> bool {
+    (modifiers.command && keycode == winit::event::VirtualKeyCode::V)
+        || (cfg!(target_os = "windows")
+            && modifiers.shift
+            && keycode == winit::event::VirtualKeyCode::Insert)
+}
+
+fn pointer_pos_in_points(
+    pos_in_pixels: winit::dpi::PhysicalPosition<f64>,
+    pixels_per_point: f32,
+    round_to_pixels: bool,
+) -> egui::Pos2 {
+    let pos_in_pixels = egui::pos2(pos_in_pixels.x as f32, pos_in_pixels.y as f32);
+    if round_to_pixels {
+        pos_in_pixels.round() / pixels_per_point
+    } else {
+        pos_in_pixels / pixels_per_point
+    }
+}
+
+fn is_undo_command(
+    style: TextNavStyle,
+    modifiers: egui::Modifiers,
+    keycode: winit::event::VirtualKeyCode,
+) -> bool {
+    use winit::event::VirtualKeyCode;
+
+    (modifiers.matches(egui::Modifiers::COMMAND) && keycode == VirtualKeyCode::Z)
+        || (style == TextNavStyle::Windows
+            && modifiers.matches(egui::Modifiers::ALT)
+            && keycode == VirtualKeyCode::Back)
+}
+
+fn is_redo_command(
+    style: TextNavStyle,
+    modifiers: egui::Modifiers,
+    keycode: winit::event::VirtualKeyCode,
+) -> bool {
+    use egui::Modifiers;
+    use winit::event::VirtualKeyCode;
+
+    (modifiers.matches(Modifiers::COMMAND | Modifiers::SHIFT) && keycode == VirtualKeyCode::Z)
+        || (style != TextNavStyle::Mac
+            && modifiers.matches(Modifiers::COMMAND)
+            && keycode == VirtualKeyCode::Y)
+        || (style == TextNavStyle::Windows
+            && modifiers.matches(Modifiers::ALT | Modifiers::SHIFT)
+            && keycode == VirtualKeyCode::Back)
+}
+
+fn translate_mouse_button(button: winit::event::MouseButton) -> Option<egui::PointerButton> {
+    match button {
+        winit::event::MouseButton::Left => Some(egui::PointerButton::Primary),
+        winit::event::MouseButton::Right => Some(egui::PointerButton::Secondary),
+        winit::event::MouseButton::Middle => Some(egui::PointerButton::Middle),
+        winit::event::MouseButton::Other(1) => Some(egui::PointerButton::Extra1),
+        winit::event::MouseButton::Other(2) => Some(egui::PointerButton::Extra2),
+        winit::event::MouseButton::Other(_) => None,
+    }
+}
+
+/// The human-readable name of the egui [`egui::Key`] a winit keycode maps to.
+///
+/// Useful for showing key bindings in a settings UI.
+/// Returns `None` for keys egui does not know about.
+pub fn key_name(key: winit::event::VirtualKeyCode) -> Option<&'static str> {
+    translate_virtual_key_code(key).map(egui::Key::name)
+}
+
+/// Which egui [`egui::Key`] a winit keycode corresponds to, if any.
+pub fn translate_virtual_key_code(key: winit::event::VirtualKeyCode) -> Option<egui::Key> {
+    use egui::Key;
+    use winit::event::VirtualKeyCode;
+
+    Some(match key {
+        VirtualKeyCode::Down => Key::ArrowDown,
+        VirtualKeyCode::Left => Key::ArrowLeft,
+        VirtualKeyCode::Right => Key::ArrowRight,
+        VirtualKeyCode::Up => Key::ArrowUp,
+
+        VirtualKeyCode::Escape => Key::Escape,
+        VirtualKeyCode::Tab => Key::Tab,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
+        VirtualKeyCode::Space => Key::Space,
+
+        VirtualKeyCode::Insert => Key::Insert,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::PageDown => Key::PageDown,
+
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Key::Minus,
+        // Using Mac the key with the Plus sign on it is reported as the Equals key
+        // (with both English and Swedish keyboard).
+        VirtualKeyCode::Equals
+        | VirtualKeyCode::Plus
+        | VirtualKeyCode::NumpadAdd
+        | VirtualKeyCode::NumpadEquals => Key::PlusEquals,
+
+        // `Comma`, `Period`, `Semicolon`, `Slash`, `Backslash`, `LBracket`, `RBracket`,
+        // `Apostrophe` and `Grave`: `egui::Key` has no variants for these yet,
+        // so they translate to `None`.
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Key::Num0,
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Key::Num1,
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Key::Num2,
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Key::Num3,
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Key::Num4,
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Key::Num5,
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Key::Num6,
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Key::Num7,
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Key::Num8,
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Key::Num9,
+
+        VirtualKeyCode::A => Key::A,
+        VirtualKeyCode::B => Key::B,
+        VirtualKeyCode::C => Key::C,
+        VirtualKeyCode::D => Key::D,
+        VirtualKeyCode::E => Key::E,
+        VirtualKeyCode::F => Key::F,
+        VirtualKeyCode::G => Key::G,
+        VirtualKeyCode::H => Key::H,
+        VirtualKeyCode::I => Key::I,
+        VirtualKeyCode::J => Key::J,
+        VirtualKeyCode::K => Key::K,
+        VirtualKeyCode::L => Key::L,
+        VirtualKeyCode::M => Key::M,
+        VirtualKeyCode::N => Key::N,
+        VirtualKeyCode::O => Key::O,
+        VirtualKeyCode::P => Key::P,
+        VirtualKeyCode::Q => Key::Q,
+        VirtualKeyCode::R => Key::R,
+        VirtualKeyCode::S => Key::S,
+        VirtualKeyCode::T => Key::T,
+        VirtualKeyCode::U => Key::U,
+        VirtualKeyCode::V => Key::V,
+        VirtualKeyCode::W => Key::W,
+        VirtualKeyCode::X => Key::X,
+        VirtualKeyCode::Y => Key::Y,
+        VirtualKeyCode::Z => Key::Z,
+
+        VirtualKeyCode::F1 => Key::F1,
+        VirtualKeyCode::F2 => Key::F2,
+        VirtualKeyCode::F3 => Key::F3,
+        VirtualKeyCode::F4 => Key::F4,
+        VirtualKeyCode::F5 => Key::F5,
+        VirtualKeyCode::F6 => Key::F6,
+        VirtualKeyCode::F7 => Key::F7,
+        VirtualKeyCode::F8 => Key::F8,
+        VirtualKeyCode::F9 => Key::F9,
+        VirtualKeyCode::F10 => Key::F10,
+        VirtualKeyCode::F11 => Key::F11,
+        VirtualKeyCode::F12 => Key::F12,
+        VirtualKeyCode::F13 => Key::F13,
+        VirtualKeyCode::F14 => Key::F14,
+        VirtualKeyCode::F15 => Key::F15,
+        VirtualKeyCode::F16 => Key::F16,
+        VirtualKeyCode::F17 => Key::F17,
+        VirtualKeyCode::F18 => Key::F18,
+        VirtualKeyCode::F19 => Key::F19,
+        VirtualKeyCode::F20 => Key::F20,
+
+        // egui has no media or browser keys (`PlayPause`, `VolumeUp`, `NavigateBackward`, …).
+        // See `State::set_raw_keycode_sink`.
+        _ => {
+            return None;
+        }
+    })
+}
+
+fn translate_cursor(cursor_icon: egui::CursorIcon) -> Option<winit::window::CursorIcon> {
+    match cursor_icon {
+        egui::CursorIcon::None => None,
+
+        egui::CursorIcon::Alias => Some(winit::window::CursorIcon::Alias),
+        egui::CursorIcon::AllScroll => Some(winit::window::CursorIcon::AllScroll),
+        egui::CursorIcon::Cell => Some(winit::window::CursorIcon::Cell),
+        egui::CursorIcon::ContextMenu => Some(winit::window::CursorIcon::ContextMenu),
+        egui::CursorIcon::Copy => Some(winit::window::CursorIcon::Copy),
+        egui::CursorIcon::Crosshair => Some(winit::window::CursorIcon::Crosshair),
+        egui::CursorIcon::Default => Some(winit::window::CursorIcon::Default),
+        egui::CursorIcon::Grab => Some(winit::window::CursorIcon::Grab),
+        egui::CursorIcon::Grabbing => Some(winit::window::CursorIcon::Grabbing),
+        egui::CursorIcon::Help => Some(winit::window::CursorIcon::Help),
+        egui::CursorIcon::Move => Some(winit::window::CursorIcon::Move),
+        egui::CursorIcon::NoDrop => Some(winit::window::CursorIcon::NoDrop),
+        egui::CursorIcon::NotAllowed => Some(winit::window::CursorIcon::NotAllowed),
+        egui::CursorIcon::PointingHand => Some(winit::window::CursorIcon::Hand),
+        egui::CursorIcon::Progress => Some(winit::window::CursorIcon::Progress),
+
+        egui::CursorIcon::ResizeHorizontal => Some(winit::window::CursorIcon::EwResize),
+        egui::CursorIcon::ResizeNeSw => Some(winit::window::CursorIcon::NeswResize),
+        egui::CursorIcon::ResizeNwSe => Some(winit::window::CursorIcon::NwseResize),
+        egui::CursorIcon::ResizeVertical => Some(winit::window::CursorIcon::NsResize),
+
+        egui::CursorIcon::ResizeEast => Some(winit::window::CursorIcon::EResize),
+        egui::CursorIcon::ResizeSouthEast => Some(winit::window::CursorIcon::SeResize),
+        egui::CursorIcon::ResizeSouth => Some(winit::window::CursorIcon::SResize),
+        egui::CursorIcon::ResizeSouthWest => Some(winit::window::CursorIcon::SwResize),
+        egui::CursorIcon::ResizeWest => Some(winit::window::CursorIcon::WResize),
+        egui::CursorIcon::ResizeNorthWest => Some(winit::window::CursorIcon::NwResize),
+        egui::CursorIcon::ResizeNorth => Some(winit::window::CursorIcon::NResize),
+        egui::CursorIcon::ResizeNorthEast => Some(winit::window::CursorIcon::NeResize),
+        egui::CursorIcon::ResizeColumn => Some(winit::window::CursorIcon::ColResize),
+        egui::CursorIcon::ResizeRow => Some(winit::window::CursorIcon::RowResize),
+
+        egui::CursorIcon::Text => Some(winit::window::CursorIcon::Text),
+        egui::CursorIcon::VerticalText => Some(winit::window::CursorIcon::VerticalText),
+        egui::CursorIcon::Wait => Some(winit::window::CursorIcon::Wait),
+        egui::CursorIcon::ZoomIn => Some(winit::window::CursorIcon::ZoomIn),
+        egui::CursorIcon::ZoomOut => Some(winit::window::CursorIcon::ZoomOut),
+    }
+}
+
+// Helpers for egui Viewports
+// ---------------------------------------------------------------------------
+
+/// Apply the commands to the window.
+///
+/// A command that fails doesn't stop the rest from being applied.
+/// The commands that failed are returned together with why,
+/// e.g. so that you can retry a [`ViewportCommand::CursorGrab`].
+pub fn process_viewport_commands(
+    egui_ctx: &egui::Context,
+    info: &mut ViewportInfo,
+    commands: impl IntoIterator<Item = ViewportCommand>,
+    window: &Window,
+    is_viewport_focused: bool,
+    screenshot_requested: &mut bool,
+) -> Vec<(ViewportCommand, winit::error::ExternalError)> {
+    let mut failed = Vec::new();
+    for command in commands {
+        if let Err(err) = process_viewport_command(
+            egui_ctx,
+            window,
+            command.clone(),
+            info,
+            is_viewport_focused,
+            screenshot_requested,
+        ) {
+            failed.push((command, err));
+        }
+    }
+    failed
+}
+
+fn process_viewport_command(
+    egui_ctx: &egui::Context,
+    window: &Window,
+    command: ViewportCommand,
+    info: &mut ViewportInfo,
+    is_viewport_focused: bool,
+    screenshot_requested: &mut bool,
+) -> Result<(), winit::error::ExternalError> {
+    crate::profile_function!();
+
+    use winit::window::ResizeDirection;
+
+    log::debug!("Processing ViewportCommand::{command:?}");
+
+    let pixels_per_point = pixels_per_point(egui_ctx, window);
+
+    match command {
+        ViewportCommand::Close => {
+            info.events.push(egui::ViewportEvent::Close);
+        }
+        ViewportCommand::CancelClose => {
+            // Need to be handled elsewhere
+        }
+        ViewportCommand::StartDrag => {
+            // If `is_viewport_focused` is not checked on x11 the input will be permanently taken until the app is killed!
+
+            // TODO: check that the left mouse-button was pressed down recently,
+            // or we will have bugs on Windows.
+            // See https://github.com/emilk/egui/pull/1108
+            if is_viewport_focused {
+                window.drag_window()?;
+            }
+        }
+        ViewportCommand::InnerSize(size) => {
+            let width_px = pixels_per_point * size.x.max(1.0);
+            let height_px = pixels_per_point * size.y.max(1.0);
+            window.set_inner_size(PhysicalSize::new(width_px, height_px));
+        }
+        ViewportCommand::BeginResize(direction) => {
+            window.drag_resize_window(match direction {
+                egui::viewport::ResizeDirection::North => ResizeDirection::North,
+                egui::viewport::ResizeDirection::South => ResizeDirection::South,
+                egui::viewport::ResizeDirection::West => ResizeDirection::West,
+                egui::viewport::ResizeDirection::NorthEast => ResizeDirection::NorthEast,
+                egui::viewport::ResizeDirection::SouthEast => ResizeDirection::SouthEast,
+                egui::viewport::ResizeDirection::NorthWest => ResizeDirection::NorthWest,
+                egui::viewport::ResizeDirection::SouthWest => ResizeDirection::SouthWest,
+            })?;
+        }
+        ViewportCommand::Title(title) => {
+            window.set_title(&title);
+        }
+        ViewportCommand::Transparent(v) => window.set_transparent(v),
+        ViewportCommand::Visible(v) => window.set_visible(v),
+        ViewportCommand::OuterPosition(pos) => {
+            window.set_outer_position(PhysicalPosition::new(
+                pixels_per_point * pos.x,
+                pixels_per_point * pos.y,
+            ));
+        }
+        ViewportCommand::MinInnerSize(s) => {
+            window.set_min_inner_size((s.is_finite() && s != Vec2::ZERO).then_some(
+                PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y),
+            ));
+        }
+        ViewportCommand::MaxInnerSize(s) => {
+            window.set_max_inner_size((s.is_finite() && s != Vec2::INFINITY).then_some(
+                PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y),
+            ));
+        }
+        ViewportCommand::ResizeIncrements(s) => {
+            window.set_resize_increments(
+                s.map(|s| PhysicalSize::new(pixels_per_point * s.x, pixels_per_point * s.y)),
+            );
+        }
+        ViewportCommand::Resizable(v) => window.set_resizable(v),
+        ViewportCommand::EnableButtons {
+            close,
+            minimized,
+            maximize,
+        } => window.set_enabled_buttons(
+            if close {
+                WindowButtons::CLOSE
+            } else {
+                WindowButtons::empty()
+            } | if minimized {
+                WindowButtons::MINIMIZE
+            } else {
+                WindowButtons::empty()
+            } | if maximize {
+                WindowButtons::MAXIMIZE
+            } else {
+                WindowButtons::empty()
+            },
+        ),
+        ViewportCommand::Minimized(v) => {
+            window.set_minimized(v);
+            info.minimized = Some(v);
+        }
+        ViewportCommand::Maximized(v) => {
+            window.set_maximized(v);
+            info.maximized = Some(v);
+        }
+        ViewportCommand::Fullscreen(v) => {
+            window.set_fullscreen(v.then_some(winit::window::Fullscreen::Borderless(None)));
+        }
+        ViewportCommand::Decorations(v) => window.set_decorations(v),
+        ViewportCommand::WindowLevel(l) => window.set_window_level(match l {
+            egui::viewport::WindowLevel::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
+            egui::viewport::WindowLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
+            egui::viewport::WindowLevel::Normal => WindowLevel::Normal,
+        }),
+        ViewportCommand::Icon(icon) => {
+            window.set_window_icon(icon.map(|icon| {
+                winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
+                    .expect("Invalid ICON data!")
+            }));
+        }
+        ViewportCommand::IMEPosition(pos) => {
+            window.set_ime_position(PhysicalPosition::new(
+                pixels_per_point * pos.x,
+                pixels_per_point * pos.y,
+            ));
+        }
+        ViewportCommand::IMEAllowed(v) => window.set_ime_allowed(v),
+        ViewportCommand::IMEPurpose(p) => window.set_ime_purpose(match p {
+            egui::viewport::IMEPurpose::Password => winit::window::ImePurpose::Password,
+            egui::viewport::IMEPurpose::Terminal => winit::window::ImePurpose::Terminal,
+            egui::viewport::IMEPurpose::Normal => winit::window::ImePurpose::Normal,
+        }),
+        ViewportCommand::Focus => {
+            if !window.has_focus() {
+                window.focus_window();
+            }
+        }
+        ViewportCommand::RequestUserAttention(a) => {
+            window.request_user_attention(translate_user_attention(a));
+        }
+        ViewportCommand::SetTheme(t) => window.set_theme(match t {
+            egui::SystemTheme::Light => Some(winit::window::Theme::Light),
+            egui::SystemTheme::Dark => Some(winit::window::Theme::Dark),
+            egui::SystemTheme::SystemDefault => None,
+        }),
+        ViewportCommand::ContentProtected(v) => window.set_content_protected(v),
+        ViewportCommand::CursorPosition(pos) => {
+            warp_cursor(window, pixels_per_point, pos)?;
+        }
+        ViewportCommand::CursorGrab(o) => {
+            window.set_cursor_grab(match o {
+                egui::viewport::CursorGrab::None => CursorGrabMode::None,
+                egui::viewport::CursorGrab::Confined => CursorGrabMode::Confined,
+                egui::viewport::CursorGrab::Locked => CursorGrabMode::Locked,
+            })?;
+        }
+        ViewportCommand::CursorVisible(v) => window.set_cursor_visible(v),
+        ViewportCommand::MousePassthrough(passthrough) => {
+            window.set_cursor_hittest(!passthrough)?;
+        }
+        ViewportCommand::Screenshot => {
+            *screenshot_requested = true;
+        }
+    }
+
+    Ok(())
+}
+
+/// Build and intitlaize a window.
+///
+/// Wrapper around `create_winit_window_builder` and `apply_viewport_builder_to_window`.
+pub fn create_window<T>(
+    egui_ctx: &egui::Context,
+    event_loop: &EventLoopWindowTarget<T>,
+    viewport_builder: &ViewportBuilder,
+) -> Result<Window, winit::error::OsError> {
+    crate::profile_function!();
+
+    let window_builder =
+        create_winit_window_builder(egui_ctx, event_loop, viewport_builder.clone());
+    let window = {
+        crate::profile_scope!("WindowBuilder::build");
+        window_builder.build(event_loop)?
+    };
+    apply_viewport_builder_to_window(egui_ctx, &window, viewport_builder);
+    Ok(window)
+}
+
+pub fn create_winit_window_builder<T>(
+    egui_ctx: &egui::Context,
+    event_loop: &EventLoopWindowTarget<T>,
+    viewport_builder: ViewportBuilder,
+) -> winit::window::WindowBuilder {
+    crate::profile_function!();
+
+    // We set sizes and positions in egui:s own ui points, which depends on the egui
+    // zoom_factor and the native pixels per point, so we need to know that here.
+    // We don't know what monitor the window will appear on though, but
+    // we'll try to fix that after the window is created in the vall to `apply_viewport_builder_to_window`.
+    let native_pixels_per_point = event_loop
+        .primary_monitor()
+        .or_else(|| event_loop.available_monitors().next())
+        .map_or_else(
+            || {
+                log::debug!("Failed to find a monitor - assuming native_pixels_per_point of 1.0");
+                1.0
+            },
+            |m| m.scale_factor() as f32,
+        );
+    let zoom_factor = egui_ctx.zoom_factor();
+    let pixels_per_point = zoom_factor * native_pixels_per_point;
+
+    let ViewportBuilder {
+        title,
+        position,
+        inner_size,
+        min_inner_size,
+        max_inner_size,
+        fullscreen,
+        maximized,
+        resizable,
+        transparent,
+        decorations,
+        icon,
+        active,
+        visible,
+        close_button,
+        minimize_button,
+        maximize_button,
+        window_level,
+
+        // macOS:
+        fullsize_content_view: _fullsize_content_view,
+        title_shown: _title_shown,
+        titlebar_buttons_shown: _titlebar_buttons_shown,
+        titlebar_shown: _titlebar_shown,
+
+        // Windows:
+        drag_and_drop: _drag_and_drop,
+
+        // wayland:
+        app_id: _app_id,
+
+        mouse_passthrough: _, // handled in `apply_viewport_builder_to_window`
+    } = viewport_builder;
+
+    let mut window_builder = winit::window::WindowBuilder::new()
+        .with_title(title.unwrap_or_else(|| "egui window".to_owned()))
+        .with_transparent(transparent.unwrap_or(false))
+        .with_decorations(decorations.unwrap_or(true))
+        .with_resizable(resizable.unwrap_or(true))
+        .with_visible(visible.unwrap_or(true))
+        .with_maximized(maximized.unwrap_or(false))
+        .with_window_level(match window_level.unwrap_or_default() {
+            egui::viewport::WindowLevel::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
+            egui::viewport::WindowLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
+            egui::viewport::WindowLevel::Normal => WindowLevel::Normal,
+        })
+        .with_fullscreen(
+            fullscreen.and_then(|e| e.then_some(winit::window::Fullscreen::Borderless(None))),
+        )
+        .with_enabled_buttons({
+            let mut buttons = WindowButtons::empty();
+            if minimize_button.unwrap_or(true) {
+                buttons |= WindowButtons::MINIMIZE;
+            }
+            if maximize_button.unwrap_or(true) {
+                buttons |= WindowButtons::MAXIMIZE;
+            }
+            if close_button.unwrap_or(true) {
+                buttons |= WindowButtons::CLOSE;
+            }
+            buttons
+        })
+        .with_active(active.unwrap_or(true));
+
+    if let Some(size) = inner_size {
+        window_builder = window_builder.with_inner_size(PhysicalSize::new(
+            pixels_per_point * size.x,
+            pixels_per_point * size.y,
+        ));
+    }
+
+    if let Some(size) = min_inner_size {
+        window_builder = window_builder.with_min_inner_size(PhysicalSize::new(
+            pixels_per_point * size.x,
+            pixels_per_point * size.y,
+        ));
+    }
+
+    if let Some(size) = max_inner_size {
+        window_builder = window_builder.with_max_inner_size(PhysicalSize::new(
+            pixels_per_point * size.x,
+            pixels_per_point * size.y,
+        ));
+    }
+
+    if let Some(pos) = position {
+        window_builder = window_builder.with_position(PhysicalPosition::new(
+            pixels_per_point * pos.x,
+            pixels_per_point * pos.y,
+        ));
+    }
+
+    if let Some(icon) = icon {
+        window_builder = window_builder.with_window_icon(Some(
+            winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
+                .expect("Invalid Icon Data!"),
+        ));
+    }
+
+    #[cfg(all(feature = "wayland", target_os = "linux"))]
+    if let Some(app_id) = _app_id {
+        use winit::platform::wayland::WindowBuilderExtWayland as _;
+        window_builder = window_builder.with_name(app_id, "");
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Some(enable) = _drag_and_drop {
+        use winit::platform::windows::WindowBuilderExtWindows as _;
+        window_builder = window_builder.with_drag_and_drop(enable);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use winit::platform::macos::WindowBuilderExtMacOS as _;
+        window_builder = window_builder
+            .with_title_hidden(!_title_shown.unwrap_or(true))
+            .with_titlebar_buttons_hidden(!_titlebar_buttons_shown.unwrap_or(true))
+            .with_titlebar_transparent(!_titlebar_shown.unwrap_or(true))
+            .with_fullsize_content_view(_fullsize_content_view.unwrap_or(false));
+    }
+
+    window_builder
+}
+
+/// Applies what `create_winit_window_builder` couldn't
+pub fn apply_viewport_builder_to_window(
+    egui_ctx: &egui::Context,
+    window: &Window,
+    builder: &ViewportBuilder,
+) {
+    if let Some(mouse_passthrough) = builder.mouse_passthrough {
+        if let Err(err) = window.set_cursor_hittest(!mouse_passthrough) {
+            log::warn!("set_cursor_hittest failed: {err}");
+        }
+    }
+
+    {
+        // In `create_winit_window_builder` we didn't know
+        // on what monitor the window would appear, so we didn't know
+        // how to translate egui ui point to native physical pixels.
+        // Now we do know:
+
+        let pixels_per_point = pixels_per_point(egui_ctx, window);
+
+        if let Some(size) = builder.inner_size {
+            window.set_inner_size(PhysicalSize::new(
+                pixels_per_point * size.x,
+                pixels_per_point * size.y,
+            ));
+        }
+        if let Some(size) = builder.min_inner_size {
+            window.set_min_inner_size(Some(PhysicalSize::new(
+                pixels_per_point * size.x,
+                pixels_per_point * size.y,
+            )));
+        }
+        if let Some(size) = builder.max_inner_size {
+            window.set_max_inner_size(Some(PhysicalSize::new(
+                pixels_per_point * size.x,
+                pixels_per_point * size.y,
+            )));
+        }
+        if let Some(pos) = builder.position {
+            let pos = PhysicalPosition::new(pixels_per_point * pos.x, pixels_per_point * pos.y);
+            window.set_outer_position(pos);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+
+/// Short and fast description of an event.
+/// Useful for logging and profiling.
+pub fn short_generic_event_description<T>(event: &winit::event::Event<'_, T>) -> &'static str {
+    use winit::event::{DeviceEvent, Event, StartCause};
+
+    match event {
+        Event::Suspended => "Event::Suspended",
+        Event::Resumed => "Event::Resumed",
+        Event::MainEventsCleared => "Event::MainEventsCleared",
+        Event::RedrawRequested(_) => "Event::RedrawRequested",
+        Event::RedrawEventsCleared => "Event::RedrawEventsCleared",
+        Event::LoopDestroyed => "Event::LoopDestroyed",
+        Event::UserEvent(_) => "UserEvent",
+        Event::DeviceEvent { event, .. } => match event {
+            DeviceEvent::Added { .. } => "DeviceEvent::Added",
+            DeviceEvent::Removed { .. } => "DeviceEvent::Removed",
+            DeviceEvent::MouseMotion { .. } => "DeviceEvent::MouseMotion",
+            DeviceEvent::MouseWheel { .. } => "DeviceEvent::MouseWheel",
+            DeviceEvent::Motion { .. } => "DeviceEvent::Motion",
+            DeviceEvent::Button { .. } => "DeviceEvent::Button",
+            DeviceEvent::Key { .. } => "DeviceEvent::Key",
+            DeviceEvent::Text { .. } => "DeviceEvent::Text",
+        },
+        Event::NewEvents(start_cause) => match start_cause {
+            StartCause::ResumeTimeReached { .. } => "NewEvents::ResumeTimeReached",
+            StartCause::WaitCancelled { .. } => "NewEvents::WaitCancelled",
+            StartCause::Poll => "NewEvents::Poll",
+            StartCause::Init => "NewEvents::Init",
+        },
+        Event::WindowEvent { event, .. } => short_window_event_description(event),
+    }
+}
+
+/// Short and fast description of an event.
+/// Useful for logging and profiling.
+pub fn short_window_event_description(event: &winit::event::WindowEvent<'_>) -> &'static str {
+    use winit::event::WindowEvent;
+
+    match event {
+        WindowEvent::Resized { .. } => "WindowEvent::Resized",
+        WindowEvent::Moved { .. } => "WindowEvent::Moved",
+        WindowEvent::CloseRequested { .. } => "WindowEvent::CloseRequested",
+        WindowEvent::Destroyed { .. } => "WindowEvent::Destroyed",
+        WindowEvent::DroppedFile { .. } => "WindowEvent::DroppedFile",
+        WindowEvent::HoveredFile { .. } => "WindowEvent::HoveredFile",
+        WindowEvent::HoveredFileCancelled { .. } => "WindowEvent::HoveredFileCancelled",
+        WindowEvent::ReceivedCharacter { .. } => "WindowEvent::ReceivedCharacter",
+        WindowEvent::Focused { .. } => "WindowEvent::Focused",
+        WindowEvent::KeyboardInput { .. } => "WindowEvent::KeyboardInput",
+        WindowEvent::ModifiersChanged { .. } => "WindowEvent::ModifiersChanged",
+        WindowEvent::Ime { .. } => "WindowEvent::Ime",
+        WindowEvent::CursorMoved { .. } => "WindowEvent::CursorMoved",
+        WindowEvent::CursorEntered { .. } => "WindowEvent::CursorEntered",
+        WindowEvent::CursorLeft { .. } => "WindowEvent::CursorLeft",
+        WindowEvent::MouseWheel { .. } => "WindowEvent::MouseWheel",
+        WindowEvent::MouseInput { .. } => "WindowEvent::MouseInput",
+        WindowEvent::TouchpadMagnify { .. } => "WindowEvent::TouchpadMagnify",
+        WindowEvent::SmartMagnify { .. } => "WindowEvent::SmartMagnify",
+        WindowEvent::TouchpadRotate { .. } => "WindowEvent::TouchpadRotate",
+        WindowEvent::TouchpadPressure { .. } => "WindowEvent::TouchpadPressure",
+        WindowEvent::AxisMotion { .. } => "WindowEvent::AxisMotion",
+        WindowEvent::Touch { .. } => "WindowEvent::Touch",
+        WindowEvent::ScaleFactorChanged { .. } => "WindowEvent::ScaleFactorChanged",
+        WindowEvent::ThemeChanged { .. } => "WindowEvent::ThemeChanged",
+        WindowEvent::Occluded { .. } => "WindowEvent::Occluded",
+    }
+}
+
+/// A short, log-friendly description of an event, including its most important details,
+/// e.g. `MouseInput(Left, Pressed)`.
+///
+/// Unlike the `Debug` output this leaves out device ids and other noise.
+/// See also [`short_window_event_description`], which doesn't allocate.
+pub fn describe_window_event(event: &winit::event::WindowEvent<'_>) -> String {
+    use winit::event::{MouseScrollDelta, WindowEvent};
+
+    let name = short_window_event_description(event)
+        .strip_prefix("WindowEvent::")
+        .unwrap_or_default();
+
+    match event {
+        WindowEvent::Resized(size) => format!("{name}({}x{})", size.width, size.height),
+        WindowEvent::Moved(pos) => format!("{name}({}, {})", pos.x, pos.y),
+        WindowEvent::DroppedFile(path) | WindowEvent::HoveredFile(path) => {
+            format!("{name}({})", path.display())
+        }
+        WindowEvent::ReceivedCharacter(chr) => format!("{name}({chr:?})"),
+        WindowEvent::Focused(focused) => format!("{name}({focused})"),
+        WindowEvent::KeyboardInput { input, .. } => match input.virtual_keycode {
+            Some(keycode) => format!("{name}({keycode:?}, {:?})", input.state),
+            None => format!("{name}(scancode {}, {:?})", input.scancode, input.state),
+        },
+        WindowEvent::ModifiersChanged(modifiers) => format!("{name}({modifiers:?})"),
+        WindowEvent::Ime(ime) => format!("{name}({ime:?})"),
+        WindowEvent::CursorMoved { position, .. } => {
+            format!("{name}({:.1}, {:.1})", position.x, position.y)
+        }
+        WindowEvent::MouseWheel { delta, .. } => match delta {
+            MouseScrollDelta::LineDelta(x, y) => format!("{name}(lines {x}, {y})"),
+            MouseScrollDelta::PixelDelta(delta) => {
+                format!("{name}(pixels {:.1}, {:.1})", delta.x, delta.y)
+            }
+        },
+        WindowEvent::MouseInput { state, button, .. } => format!("{name}({button:?}, {state:?})"),
+        WindowEvent::TouchpadMagnify { delta, .. } => format!("{name}({delta})"),
+        WindowEvent::TouchpadRotate { delta, .. } => format!("{name}({delta})"),
+        WindowEvent::TouchpadPressure {
+            pressure, stage, ..
+        } => format!("{name}({pressure}, stage {stage})"),
+        WindowEvent::AxisMotion { axis, value, .. } => format!("{name}(axis {axis}, {value})"),
+        WindowEvent::Touch(touch) => format!(
+            "{name}({}, {:?}, {:.1}, {:.1})",
+            touch.id, touch.phase, touch.location.x, touch.location.y
+        ),
+        WindowEvent::ScaleFactorChanged { scale_factor, .. } => format!("{name}({scale_factor})"),
+        WindowEvent::ThemeChanged(theme) => format!("{name}({theme:?})"),
+        WindowEvent::Occluded(occluded) => format!("{name}({occluded})"),
+        WindowEvent::CloseRequested
+        | WindowEvent::Destroyed
+        | WindowEvent::HoveredFileCancelled
+        | WindowEvent::CursorEntered { .. }
+        | WindowEvent::CursorLeft { .. }
+        | WindowEvent::SmartMagnify { .. } => name.to_owned(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+
+mod profiling_scopes {
+    #![allow(unused_macros)]
+    #![allow(unused_imports)]
+
+    /// Profiling macro for feature "puffin"
+    macro_rules! profile_function {
+        ($($arg: tt)*) => {
+            #[cfg(feature = "puffin")]
+            #[cfg(not(target_arch = "wasm32"))] // Disabled on web because of the coarse 1ms clock resolution there.
+            puffin::profile_function!($($arg)*);
+        };
+    }
+    pub(crate) use profile_function;
+
+    /// Profiling macro for feature "puffin"
+    macro_rules! profile_scope {
+        ($($arg: tt)*) => {
+            #[cfg(feature = "puffin")]
+            #[cfg(not(target_arch = "wasm32"))] // Disabled on web because of the coarse 1ms clock resolution there.
+            puffin::profile_scope!($($arg)*);
+        };
+    }
+    pub(crate) use profile_scope;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoDisplay;
+
+    #[allow(unsafe_code)]
+    unsafe impl HasRawDisplayHandle for NoDisplay {
+        fn raw_display_handle(&self) -> raw_window_handle::RawDisplayHandle {
+            raw_window_handle::RawDisplayHandle::Web(raw_window_handle::WebDisplayHandle::empty())
+        }
+    }
+
+    fn test_state() -> State {
+        State::new(
+            egui::Context::default(),
+            ViewportId::ROOT,
+            &NoDisplay,
+            None,
+            None,
+        )
+    }
+
+    /// An in-memory clipboard, so that tests never touch the OS clipboard.
+    struct SharedClipboard(std::sync::Arc<egui::mutex::Mutex<String>>);
+
+    impl clipboard::ClipboardProvider for SharedClipboard {
+        fn get(&mut self) -> Option<String> {
+            Some(self.0.lock().clone())
+        }
+
+        fn set(&mut self, text: String) {
+            *self.0.lock() = text;
+        }
+    }
+
+    /// A [`test_state`] with a [`SharedClipboard`], and the contents of that clipboard.
+    fn test_state_with_clipboard() -> (State, std::sync::Arc<egui::mutex::Mutex<String>>) {
+        let contents = std::sync::Arc::new(egui::mutex::Mutex::new(String::new()));
+        let state = State::new_with_clipboard(
+            egui::Context::default(),
+            ViewportId::ROOT,
+            Box::new(SharedClipboard(contents.clone())),
+            None,
+            None,
+        );
+        (state, contents)
+    }
+
+    #[test]
+    fn test_screen_size_in_points_uses_cached_scale() {
+        let state = State::builder(egui::Context::default(), ViewportId::ROOT, &NoDisplay)
+            .with_pixels_per_point(2.0)
+            .build();
+        let size_in_pixels = egui::vec2(800.0, 600.0);
+        assert_eq!(
+            state.pixels_to_points(size_in_pixels),
+            size_in_pixels / state.pixels_per_point()
+        );
+        assert_eq!(
+            state.pixels_to_points(size_in_pixels),
+            egui::vec2(400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn test_state_builder() {
+        let state = State::builder(egui::Context::default(), ViewportId::ROOT, &NoDisplay)
+            .with_pixels_per_point(2.0)
+            .with_max_texture_side(4096)
+            .with_touch_mode(TouchMode::ForceTouch)
+            .build();
+        assert_eq!(state.pixels_per_point(), 2.0);
+        assert_eq!(state.egui_input().max_texture_side, Some(4096));
+        assert_eq!(state.touch_mode(), TouchMode::ForceTouch);
+
+        let state = test_state();
+        assert_eq!(state.pixels_per_point(), 1.0);
+        assert_eq!(state.touch_mode(), TouchMode::ForceMouse);
+    }
+
+    #[test]
+    fn test_set_focused() {
+        let mut state = test_state();
+        state.set_focused(true);
+        state.egui_input.modifiers = egui::Modifiers::CTRL;
+
+        state.set_focused(false);
+        assert!(!state.egui_input.focused);
+        assert_eq!(state.egui_input.modifiers, egui::Modifiers::NONE);
+        assert_eq!(
+            state.egui_input.events.last(),
+            Some(&egui::Event::WindowFocused(false))
+        );
+    }
+
+    #[test]
+    fn test_menu_action_shortcuts() {
+        let command = command_modifiers();
+        assert_eq!(MenuAction::Copy.shortcut(), None);
+        assert_eq!(
+            MenuAction::SelectAll.shortcut(),
+            Some(egui::KeyboardShortcut::new(command, egui::Key::A))
+        );
+        assert_eq!(
+            MenuAction::Redo.shortcut(),
+            Some(egui::KeyboardShortcut::new(
+                command | egui::Modifiers::SHIFT,
+                egui::Key::Z
+            ))
+        );
+
+        let custom = egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::F4);
+        assert_eq!(MenuAction::Shortcut(custom).shortcut(), Some(custom));
+    }
+
+    /// Run a frame of `state`'s input, with a focused [`egui::TextEdit`] editing `text`.
+    fn run_text_edit(state: &mut State, text: &mut String) {
+        let ctx = state.egui_ctx().clone();
+        let _ = ctx.run(state.take_raw_input(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.text_edit_singleline(text).request_focus();
+            });
+        });
+    }
+
+    #[test]
+    fn test_menu_action_edits_text() {
+        assert!(command_modifiers().matches(egui::Modifiers::COMMAND));
+
+        let mut state = test_state();
+        let mut text = String::new();
+        run_text_edit(&mut state, &mut text);
+
+        state
+            .egui_input
+            .events
+            .push(egui::Event::Text("hello".to_owned()));
+        run_text_edit(&mut state, &mut text);
+        assert_eq!(text, "hello");
+
+        state.on_menu_action(MenuAction::SelectAll);
+        run_text_edit(&mut state, &mut text);
+        state
+            .egui_input
+            .events
+            .push(egui::Event::Text("x".to_owned()));
+        run_text_edit(&mut state, &mut text);
+        assert_eq!(text, "x", "the selected text should be replaced");
+
+        state.on_menu_action(MenuAction::Undo);
+        run_text_edit(&mut state, &mut text);
+        assert_eq!(text, "");
+
+        state.on_menu_action(MenuAction::Redo);
+        run_text_edit(&mut state, &mut text);
+        assert_eq!(text, "x");
+    }
+
+    #[test]
+    fn test_take_pixels_per_point_changed() {
+        let mut state = test_state();
+        state.set_pixels_per_point(1.0);
+        assert_eq!(state.take_pixels_per_point_changed(), None);
+
+        state.set_pixels_per_point(2.0);
+        assert_eq!(state.pixels_per_point(), 2.0);
+        assert_eq!(state.take_pixels_per_point_changed(), Some(2.0));
+        assert_eq!(state.take_pixels_per_point_changed(), None);
+    }
+
+    #[test]
+    fn test_translate_modifiers() {
+        use winit::event::ModifiersState;
+
+        let ctrl = ModifiersState::CTRL;
+        let logo = ModifiersState::LOGO;
+
+        assert_eq!(
+            translate_modifiers(logo, TextNavStyle::Mac),
+            egui::Modifiers::MAC_CMD | egui::Modifiers::COMMAND
+        );
+        assert_eq!(
+            translate_modifiers(ctrl, TextNavStyle::Mac),
+            egui::Modifiers::CTRL
+        );
+
+        assert_eq!(
+            translate_modifiers(ctrl, TextNavStyle::Windows),
+            egui::Modifiers::CTRL | egui::Modifiers::COMMAND
+        );
+        assert_eq!(
+            translate_modifiers(logo, TextNavStyle::Windows),
+            egui::Modifiers::NONE
+        );
+
+        assert_eq!(
+            translate_modifiers(ctrl, TextNavStyle::Emacs),
+            egui::Modifiers::CTRL
+        );
+        assert_eq!(
+            translate_modifiers(logo, TextNavStyle::Emacs),
+            egui::Modifiers::COMMAND
+        );
+        assert_eq!(
+            translate_modifiers(
+                ModifiersState::ALT | ModifiersState::SHIFT,
+                TextNavStyle::Emacs
+            ),
+            egui::Modifiers::ALT | egui::Modifiers::SHIFT
+        );
+    }
+
+    #[test]
+    fn test_cached_context_interaction() {
+        let mut state = test_state();
+        let ctx = state.egui_ctx().clone();
+
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.text_edit_singleline(&mut String::new()).request_focus();
+            });
+        });
+        state.cache_context_interaction();
+
+        assert_eq!(state.wants_pointer_input(), ctx.wants_pointer_input());
+        assert_eq!(state.wants_keyboard_input(), ctx.wants_keyboard_input());
+        assert!(state.wants_keyboard_input());
+        assert_eq!(state.is_using_pointer(), ctx.is_using_pointer());
+    }
+
+    #[test]
+    fn test_mouse_button_mapping() {
+        use winit::event::{ElementState, MouseButton};
+
+        let mut state = test_state();
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        state.set_mouse_button_mapping([
+            (1, egui::PointerButton::Extra2),
+            (5, egui::PointerButton::Extra1),
+        ]);
+
+        for button in [
+            MouseButton::Other(5),
+            MouseButton::Other(1),
+            MouseButton::Other(2),
+            MouseButton::Other(6),
+        ] {
+            state.on_mouse_button_input(ElementState::Pressed, button);
+        }
+
+        let buttons: Vec<_> = state
+            .egui_input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::PointerButton { button, .. } => Some(*button),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            buttons,
+            [
+                egui::PointerButton::Extra1,
+                egui::PointerButton::Extra2,
+                egui::PointerButton::Extra2,
+            ],
+            "unmapped buttons are ignored"
+        );
+    }
+
+    #[test]
+    fn test_single_pointer_button() {
+        use winit::event::{ElementState, MouseButton};
+
+        let mut state = test_state();
+        state.set_single_pointer_button(true);
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Right);
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Right);
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+
+        let buttons: Vec<_> = state
+            .egui_input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::PointerButton {
+                    button, pressed, ..
+                } => Some((*button, *pressed)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            buttons,
+            [
+                (egui::PointerButton::Primary, true),
+                (egui::PointerButton::Primary, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trigger_clipboard_action() {
+        let (mut state, contents) = test_state_with_clipboard();
+        *contents.lock() = "pasted\r\ntext".to_owned();
+
+        state.trigger_clipboard_action(ClipboardAction::Copy);
+        state.trigger_clipboard_action(ClipboardAction::Paste);
+        assert_eq!(
+            state.egui_input.events,
+            [
+                egui::Event::Copy,
+                egui::Event::Paste("pasted\ntext".to_owned())
+            ]
+        );
+        assert_eq!(state.last_paste_source(), Some(PasteSource::Clipboard));
+
+        state.inject_paste("injected".to_owned());
+        assert_eq!(state.last_paste_source(), Some(PasteSource::Injected));
+    }
+
+    #[test]
+    fn test_paste_image() {
+        struct TextAndImage(String);
+
+        impl clipboard::ClipboardProvider for TextAndImage {
+            fn get(&mut self) -> Option<String> {
+                Some(self.0.clone())
+            }
+
+            fn set(&mut self, _text: String) {}
+
+            fn get_image(&mut self) -> Option<egui::ColorImage> {
+                Some(egui::ColorImage::new([2, 2], egui::Color32::RED))
+            }
+        }
+
+        let new_state = |text: &str| {
+            State::new_with_clipboard(
+                egui::Context::default(),
+                ViewportId::ROOT,
+                Box::new(TextAndImage(text.to_owned())),
+                None,
+                None,
+            )
+        };
+
+        // Only an image on the clipboard:
+        let mut state = new_state("");
+        state.trigger_clipboard_action(ClipboardAction::Paste);
+        assert_eq!(
+            state.egui_input.events,
+            [egui::Event::PasteImage(std::sync::Arc::new(
+                egui::ColorImage::new([2, 2], egui::Color32::RED)
+            ))]
+        );
+        assert_eq!(state.last_paste_source(), Some(PasteSource::Clipboard));
+
+        // Text wins over the image:
+        let mut state = new_state("text");
+        state.trigger_clipboard_action(ClipboardAction::Paste);
+        assert_eq!(
+            state.egui_input.events,
+            [egui::Event::Paste("text".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_current_drag() {
+        use winit::event::{ElementState, MouseButton};
+
+        let a = egui::pos2(10.0, 20.0);
+        let b = egui::pos2(30.0, 40.0);
+
+        let mut state = test_state();
+        state.pointer_pos_in_points = Some(a);
+        assert_eq!(state.current_drag(), None);
+
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        state.pointer_pos_in_points = Some(b);
+        assert_eq!(
+            state.current_drag(),
+            Some((a, b, egui::PointerButton::Primary))
+        );
+
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+        assert_eq!(state.current_drag(), None);
+    }
+
+    #[test]
+    fn test_remote_click_matches_local_click() {
+        use winit::event::{ElementState, MouseButton};
+
+        let pos = egui::pos2(10.0, 20.0);
+
+        let mut local = test_state();
+        local.on_pointer_moved(pos);
+        local.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        local.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+
+        let mut remote = test_state();
+        let _ = remote.on_remote_event(RemoteEvent::PointerMoved(pos));
+        let _ = remote.on_remote_event(RemoteEvent::PointerButton {
+            button: egui::PointerButton::Primary,
+            pressed: true,
+        });
+        let _ = remote.on_remote_event(RemoteEvent::PointerButton {
+            button: egui::PointerButton::Primary,
+            pressed: false,
+        });
+
+        assert_eq!(remote.egui_input.events, local.egui_input.events);
+        assert_eq!(remote.egui_input.events.len(), 3);
+    }
+
+    #[test]
+    fn test_remote_scroll_matches_local_scroll() {
+        let mut local = test_state();
+        local.on_mouse_wheel(winit::event::MouseScrollDelta::LineDelta(0.0, 1.0));
+
+        let mut remote = test_state();
+        let _ = remote.on_remote_event(RemoteEvent::Scroll {
+            unit: egui::MouseWheelUnit::Line,
+            delta: egui::vec2(0.0, 1.0),
+        });
+
+        assert_eq!(remote.egui_input.events, local.egui_input.events);
+    }
+
+    #[test]
+    fn test_remote_page_scroll() {
+        let mut state = test_state();
+        let ctx = state.egui_ctx().clone();
+        let _ = ctx.run(
+            egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(800.0, 600.0),
+                )),
+                ..Default::default()
+            },
+            |_| {},
+        );
+
+        let _ = state.on_remote_event(RemoteEvent::Scroll {
+            unit: egui::MouseWheelUnit::Page,
+            delta: egui::vec2(0.0, -1.0),
+        });
+        assert_eq!(
+            state.egui_input.events.last(),
+            Some(&egui::Event::Scroll(egui::vec2(0.0, -600.0)))
+        );
+    }
+
+    #[test]
+    fn test_set_clipboard_display() {
+        struct NeedsDisplay(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+        impl clipboard::ClipboardProvider for NeedsDisplay {
+            fn get(&mut self) -> Option<String> {
+                None
+            }
+
+            fn set(&mut self, _text: String) {}
+
+            fn init_display(&mut self, _display_target: &dyn HasRawDisplayHandle) {
+                self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+
+        let initialized = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut state = State::new_with_clipboard(
+            egui::Context::default(),
+            ViewportId::ROOT,
+            Box::new(NeedsDisplay(initialized.clone())),
+            None,
+            None,
+        );
+        assert!(!initialized.load(std::sync::atomic::Ordering::Relaxed));
+        state.set_clipboard_display(&NoDisplay);
+        assert!(initialized.load(std::sync::atomic::Ordering::Relaxed));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_middle_click_pastes_primary_selection() {
+        struct PrimaryOnly;
+
+        impl clipboard::ClipboardProvider for PrimaryOnly {
+            fn get(&mut self) -> Option<String> {
+                None
+            }
+
+            fn set(&mut self, _text: String) {}
+
+            fn get_primary(&mut self) -> Option<String> {
+                Some("selected".to_owned())
+            }
+        }
+
+        let mut state = State::new_with_clipboard(
+            egui::Context::default(),
+            ViewportId::ROOT,
+            Box::new(PrimaryOnly),
+            None,
+            None,
+        );
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        let ctx = state.egui_ctx().clone();
+
+        // Nothing to paste into:
+        state.on_pointer_button(egui::PointerButton::Middle, true);
+        state.on_pointer_button(egui::PointerButton::Middle, false);
+        assert_eq!(state.last_paste_source(), None);
+
+        let run_with_text_edit = |event| {
+            let input = egui::RawInput {
+                events: vec![event],
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.text_edit_singleline(&mut String::new()).request_focus();
+                });
+            });
+        };
+
+        // A text field has focus, but the pointer is not over egui:
+        run_with_text_edit(egui::Event::PointerGone);
+        state.on_pointer_button(egui::PointerButton::Middle, true);
+        state.on_pointer_button(egui::PointerButton::Middle, false);
+        assert_eq!(state.last_paste_source(), None);
+
+        run_with_text_edit(egui::Event::PointerMoved(egui::pos2(10.0, 10.0)));
+        state.egui_input.events.clear();
+        state.on_pointer_button(egui::PointerButton::Middle, true);
+        assert!(state
+            .egui_input
+            .events
+            .contains(&egui::Event::Paste("selected".to_owned())));
+        assert_eq!(state.last_paste_source(), Some(PasteSource::Primary));
+    }
+
+    #[test]
+    fn test_custom_clipboard() {
+        let contents = std::sync::Arc::new(egui::mutex::Mutex::new("from the host".to_owned()));
+        let mut state = State::new_with_clipboard(
+            egui::Context::default(),
+            ViewportId::ROOT,
+            Box::new(SharedClipboard(contents.clone())),
+            None,
+            None,
+        );
+
+        state.trigger_clipboard_action(ClipboardAction::Paste);
+        assert_eq!(
+            state.egui_input.events,
+            [egui::Event::Paste("from the host".to_owned())]
+        );
+
+        state.clipboard.set("from egui".to_owned());
+        assert_eq!(*contents.lock(), "from egui");
+    }
+
+    #[test]
+    fn test_platform_output_options() {
+        fn apply(
+            options: PlatformOutputOptions,
+        ) -> (PlatformOutputActions, String, Vec<String>, bool) {
+            let (mut state, contents) = test_state_with_clipboard();
+            let opened = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+            state.set_open_url_handler({
+                let opened = opened.clone();
+                move |open_url| opened.lock().push(open_url.url.clone())
+            });
+            state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+            state.set_platform_output_options(options);
+
+            let actions = state.apply_platform_output(
+                None,
+                egui::PlatformOutput {
+                    cursor_icon: egui::CursorIcon::Text,
+                    open_url: Some(egui::OpenUrl::same_tab("https://www.egui.rs")),
+                    copied_text: "copied".to_owned(),
+                    text_cursor_pos: Some(egui::pos2(1.0, 2.0)),
+                    ..Default::default()
+                },
+            );
+            let contents = contents.lock().clone();
+            let opened = opened.lock().clone();
+            (actions, contents, opened, state.allow_ime)
+        }
+
+        let everything = PlatformOutputActions {
+            cursor_changed: true,
+            opened_url: Some("https://www.egui.rs".to_owned()),
+            copied: true,
+            ime_moved: true,
+        };
+        let url = vec!["https://www.egui.rs".to_owned()];
+
+        let (actions, contents, opened, allow_ime) = apply(Default::default());
+        assert_eq!(actions, everything);
+        assert_eq!(
+            (contents.as_str(), &opened, allow_ime),
+            ("copied", &url, true)
+        );
+
+        let (actions, ..) = apply(PlatformOutputOptions {
+            apply_cursor: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            actions,
+            PlatformOutputActions {
+                cursor_changed: false,
+                ..everything.clone()
+            }
+        );
+
+        let (actions, contents, ..) = apply(PlatformOutputOptions {
+            apply_clipboard: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            actions,
+            PlatformOutputActions {
+                copied: false,
+                ..everything.clone()
+            }
+        );
+        assert_eq!(contents, "");
+
+        let (actions, _, opened, _) = apply(PlatformOutputOptions {
+            apply_open_url: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            actions,
+            PlatformOutputActions {
+                opened_url: None,
+                ..everything.clone()
+            }
+        );
+        assert!(opened.is_empty());
+
+        let (actions, .., allow_ime) = apply(PlatformOutputOptions {
+            apply_ime: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            actions,
+            PlatformOutputActions {
+                ime_moved: false,
+                ..everything
+            }
+        );
+        assert!(!allow_ime);
+    }
+
+    #[test]
+    fn test_copy_text() {
+        let (mut state, contents) = test_state_with_clipboard();
+        assert!(!state.copy_text(String::new()));
+
+        assert!(state.copy_text("copied".to_owned()));
+        assert_eq!(*contents.lock(), "copied");
+    }
+
+    #[test]
+    fn test_points_per_scroll_line() {
+        let mut state = test_state();
+        state.set_points_per_scroll_line(20.0);
+        state.on_mouse_wheel(winit::event::MouseScrollDelta::LineDelta(1.0, 2.0));
+        assert_eq!(
+            state.egui_input.events.last(),
+            Some(&egui::Event::Scroll(egui::vec2(20.0, 40.0)))
+        );
+    }
+
+    #[test]
+    fn test_ctrl_scroll_zoom_divisor() {
+        let zoom = |divisor| {
+            let mut state = test_state();
+            state.set_ctrl_scroll_zoom_divisor(divisor);
+            state.egui_input.modifiers = egui::Modifiers::CTRL | egui::Modifiers::COMMAND;
+            state.on_mouse_wheel(winit::event::MouseScrollDelta::LineDelta(0.0, 1.0));
+            match state.egui_input.events.last() {
+                Some(egui::Event::Zoom(factor)) => *factor,
+                _ => panic!("expected a zoom"),
+            }
+        };
+        assert!(1.0 < zoom(400.0));
+        assert!(zoom(400.0) < zoom(200.0));
+    }
+
+    #[test]
+    fn test_scroll_zoom_factor() {
+        let scroll_zoom_factor = |delta_y, is_pixel_delta| {
+            scroll_zoom_factor(
+                delta_y,
+                is_pixel_delta,
+                POINTS_PER_SCROLL_LINE,
+                CTRL_SCROLL_ZOOM_DIVISOR,
+            )
+        };
+
+        let max_factor = scroll_zoom_factor(POINTS_PER_SCROLL_LINE, false);
+        assert_eq!(scroll_zoom_factor(10_000.0, true), max_factor);
+        assert!((scroll_zoom_factor(-10_000.0, true) - 1.0 / max_factor).abs() < 1e-6);
+        assert_eq!(scroll_zoom_factor(0.0, true), 1.0);
+        assert!(scroll_zoom_factor(2.0 * POINTS_PER_SCROLL_LINE, false) > max_factor);
+
+        // The clamp follows the configured scroll line:
+        let mut state = test_state();
+        state.set_points_per_scroll_line(20.0);
+        state.egui_input.modifiers = egui::Modifiers::COMMAND;
+        state.on_mouse_wheel(winit::event::MouseScrollDelta::PixelDelta(
+            winit::dpi::PhysicalPosition::new(0.0, 10_000.0),
+        ));
+        let zoom = state
+            .egui_input
+            .events
+            .iter()
+            .find_map(|event| match event {
+                egui::Event::Zoom(factor) => Some(*factor),
+                _ => None,
+            });
+        assert_eq!(zoom, Some((20.0 / CTRL_SCROLL_ZOOM_DIVISOR).exp()));
+    }
+
+    #[test]
+    fn test_axis_pointer() {
+        let mut state = test_state();
+        assert!(!state.on_axis_motion(0, 5.0), "ignored by default");
+        assert!(state.egui_input.events.is_empty());
+
+        state.set_axis_pointer(Some(AxisPointerConfig {
+            points_per_unit: 2.0,
+            ..Default::default()
+        }));
+        state.pointer_pos_in_points = Some(egui::pos2(10.0, 10.0));
+        assert!(state.on_axis_motion(0, 5.0));
+        assert!(state.on_axis_motion(1, -1.0));
+        assert!(!state.on_axis_motion(2, 5.0), "unmapped axis");
+        assert_eq!(state.pointer_pos_in_points(), Some(egui::pos2(20.0, 8.0)));
+        assert_eq!(
+            state.egui_input.events,
+            [
+                egui::Event::PointerMoved(egui::pos2(20.0, 10.0)),
+                egui::Event::PointerMoved(egui::pos2(20.0, 8.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_force_curve() {
+        use winit::event::Force;
+
+        let mut state = test_state();
+        assert_eq!(state.touch_force(Some(Force::Normalized(0.25))), Some(0.25));
+
+        state.set_force_curve(|force| force.sqrt());
+        assert_eq!(state.touch_force(Some(Force::Normalized(0.25))), Some(0.5));
+        assert_eq!(
+            state.touch_force(Some(Force::Calibrated {
+                force: 1.0,
+                max_possible_force: 4.0,
+                altitude_angle: None,
+            })),
+            Some(0.5)
+        );
+        assert_eq!(state.touch_force(None), None);
+    }
+
+    #[test]
+    fn test_disallow_gui_zoom() {
+        let mut state = test_state();
+        state.egui_input.modifiers = egui::Modifiers::CTRL | egui::Modifiers::COMMAND;
+        let delta = winit::event::MouseScrollDelta::LineDelta(0.0, 1.0);
+
+        state.on_mouse_wheel(delta);
+        assert!(matches!(
+            state.egui_input.events.last(),
+            Some(egui::Event::Zoom(_))
+        ));
+
+        state.set_allow_gui_zoom(false);
+        state.on_mouse_wheel(delta);
+        assert_eq!(
+            state.egui_input.events.last(),
+            Some(&egui::Event::Scroll(egui::vec2(
+                0.0,
+                POINTS_PER_SCROLL_LINE
+            )))
+        );
+    }
+
+    #[test]
+    fn test_copy_newline_style() {
+        let text = || "a\nb\r\nc".to_owned();
+        assert_eq!(with_newline_style(text(), NewlineStyle::Lf), "a\nb\r\nc");
+        assert_eq!(
+            with_newline_style(text(), NewlineStyle::CrLf),
+            "a\r\nb\r\nc"
+        );
+
+        let platform = with_newline_style("a\nb".to_owned(), NewlineStyle::Platform);
+        if cfg!(windows) {
+            assert_eq!(platform, "a\r\nb");
+        } else {
+            assert_eq!(platform, "a\nb");
+        }
+    }
+
+    #[test]
+    fn test_is_composing() {
+        use winit::event::Ime;
+
+        let mut state = test_state();
+        assert!(!state.is_composing());
+
+        state.on_ime(&Ime::Enabled);
+        assert!(!state.is_composing());
+
+        state.on_ime(&Ime::Preedit("か".to_owned(), Some((0, 3))));
+        assert!(state.is_composing());
+
+        state.on_ime(&Ime::Commit("か".to_owned()));
+        assert!(!state.is_composing());
+    }
+
+    #[test]
+    fn test_multi_file_drop() {
+        let mut state = test_state();
+        for path in ["a.txt", "b.txt"] {
+            state.egui_input.hovered_files.push(egui::HoveredFile {
+                path: Some(path.into()),
+                ..Default::default()
+            });
+        }
+
+        state.on_dropped_file("a.txt".as_ref());
+        state.on_dropped_file("b.txt".as_ref());
+
+        assert!(state.egui_input.hovered_files.is_empty());
+        assert_eq!(state.egui_input.dropped_files.len(), 2);
+        assert_eq!(state.last_drop_count(), 2);
+
+        state.egui_input.take();
+        state.on_dropped_file("c.txt".as_ref());
+        assert_eq!(state.last_drop_count(), 1);
+    }
+
+    #[test]
+    fn test_interleaved_file_drop() {
+        let mut state = test_state();
+        let hover = |state: &mut State, path: &str| {
+            state.egui_input.hovered_files.push(egui::HoveredFile {
+                path: Some(path.into()),
+                ..Default::default()
+            });
+        };
+
+        hover(&mut state, "a.txt");
+        hover(&mut state, "b.txt");
+        state.on_dropped_file("a.txt".as_ref());
+        assert_eq!(
+            state.egui_input.hovered_files.len(),
+            1,
+            "b.txt is still being dropped"
+        );
+
+        hover(&mut state, "c.txt");
+        state.on_dropped_file("b.txt".as_ref());
+        state.on_dropped_file("c.txt".as_ref());
+        assert!(state.egui_input.hovered_files.is_empty());
+        assert_eq!(state.egui_input.dropped_files.len(), 3);
+    }
+
+    #[test]
+    fn test_horizontal_scroll_modifier() {
+        fn scroll(state: &mut State, modifiers: egui::Modifiers) -> egui::Event {
+            state.egui_input.modifiers = modifiers;
+            state.on_mouse_wheel(winit::event::MouseScrollDelta::LineDelta(0.0, 1.0));
+            state.egui_input.events.pop().unwrap()
+        }
+
+        let vertical = egui::Event::Scroll(egui::vec2(0.0, POINTS_PER_SCROLL_LINE));
+        let horizontal = egui::Event::Scroll(egui::vec2(POINTS_PER_SCROLL_LINE, 0.0));
+
+        let mut state = test_state();
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::SHIFT),
+            vertical,
+            "opt-in"
+        );
+
+        state.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
+        assert_eq!(scroll(&mut state, egui::Modifiers::SHIFT), horizontal);
+        assert_eq!(scroll(&mut state, egui::Modifiers::ALT), vertical);
+
+        state.set_horizontal_scroll_modifier(Some(egui::Modifiers::ALT));
+        assert_eq!(scroll(&mut state, egui::Modifiers::ALT), horizontal);
+        assert_eq!(scroll(&mut state, egui::Modifiers::SHIFT), vertical);
+
+        state.set_horizontal_scroll_modifier(None);
+        assert_eq!(scroll(&mut state, egui::Modifiers::SHIFT), vertical);
+        assert_eq!(scroll(&mut state, egui::Modifiers::ALT), vertical);
+    }
+
+    #[test]
+    fn test_two_axis_scroll() {
+        fn scroll(state: &mut State, modifiers: egui::Modifiers, x: f32, y: f32) -> egui::Event {
+            state.egui_input.modifiers = modifiers;
+            state.on_mouse_wheel(winit::event::MouseScrollDelta::PixelDelta(
+                winit::dpi::PhysicalPosition::new(x as f64, y as f64),
+            ));
+            state.egui_input.events.pop().unwrap()
+        }
+
+        let diagonal = egui::Event::Scroll(egui::vec2(3.0, 4.0));
+
+        let mut state = test_state();
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::NONE, 3.0, 4.0),
+            diagonal
+        );
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::SHIFT, 3.0, 4.0),
+            diagonal
+        );
+
+        // With the remap, genuine horizontal scrolling is not counted twice:
+        state.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::SHIFT, 3.0, 4.0),
+            diagonal
+        );
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::SHIFT, 3.0, 0.0),
+            egui::Event::Scroll(egui::vec2(3.0, 0.0))
+        );
+        assert_eq!(
+            scroll(&mut state, egui::Modifiers::SHIFT, 0.0, 4.0),
+            egui::Event::Scroll(egui::vec2(4.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_simulate_ime() {
+        let mut state = test_state();
+        state.simulate_ime("日本");
+        assert_eq!(
+            state.egui_input.events,
+            [
+                egui::Event::CompositionStart,
+                egui::Event::CompositionUpdate("日本".to_owned()),
+                egui::Event::CompositionEnd("日本".to_owned()),
+            ]
+        );
+        assert!(!state.is_composing());
+    }
+
+    #[test]
+    fn test_ime_commit_as_text() {
+        let mut state = test_state();
+        state.set_ime_commit_as_text(true);
+        state.on_ime(&winit::event::Ime::Commit("日本".to_owned()));
+        assert_eq!(
+            state.egui_input.events,
+            [
+                egui::Event::CompositionEnd("日本".to_owned()),
+                egui::Event::Text("日本".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scale_change_debounce() {
+        use std::time::Duration;
+
+        let mut state = test_state();
+        state.set_scale_change_debounce(Some(Duration::from_millis(100)));
+        let start = web_time::Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(state.debounce_scale_change(1.0, at(0)), 1.0);
+
+        state.last_scale_change = Some(at(10));
+        assert_eq!(state.debounce_scale_change(1.5, at(10)), 1.0);
+        state.last_scale_change = Some(at(20));
+        assert_eq!(state.debounce_scale_change(2.0, at(20)), 1.0);
+        assert_eq!(state.debounce_scale_change(2.0, at(110)), 1.0);
+
+        assert_eq!(state.debounce_scale_change(2.0, at(120)), 2.0);
+        assert_eq!(state.debounce_scale_change(2.0, at(130)), 2.0);
+    }
+
+    #[test]
+    fn test_window_scale_factor() {
+        let mut state = test_state();
+        let ctx = state.egui_ctx().clone();
+        ctx.set_zoom_factor(1.5);
+        let _ = ctx.run(Default::default(), |_| {});
+
+        state.set_window_scale_factor(2.0);
+        assert_eq!(state.window_scale_factor(), 2.0);
+        assert_eq!(
+            state.pixels_per_point(),
+            state.window_scale_factor() * ctx.zoom_factor()
+        );
+        assert_eq!(state.pixels_per_point(), 3.0);
+    }
+
+    #[test]
+    fn test_clamp_to_window_px() {
+        let size = egui::vec2(800.0, 600.0);
+        let clamp = |x, y| clamp_to_window_px(egui::vec2(x, y), size);
+        assert_eq!(clamp(100.0, 200.0), egui::vec2(100.0, 200.0));
+        assert_eq!(clamp(-50.0, 1000.0), egui::vec2(0.0, 599.0));
+        assert_eq!(clamp(5000.0, -1.0), egui::vec2(799.0, 0.0));
+        assert_eq!(
+            clamp_to_window_px(egui::vec2(10.0, 10.0), egui::Vec2::ZERO),
+            egui::Vec2::ZERO,
+            "minimized window"
+        );
+    }
+
+    #[test]
+    fn test_scroll_phase() {
+        use winit::event::{MouseScrollDelta, TouchPhase};
+
+        let pixels = MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(0.0, 3.0));
+        let lines = MouseScrollDelta::LineDelta(0.0, 1.0);
+
+        assert_eq!(
+            scroll_phase(pixels, TouchPhase::Started),
+            Some(egui::TouchPhase::Start)
+        );
+        assert_eq!(
+            scroll_phase(pixels, TouchPhase::Ended),
+            Some(egui::TouchPhase::End)
+        );
+        assert_eq!(scroll_phase(lines, TouchPhase::Moved), None);
+    }
+
+    #[test]
+    fn test_pressed_keys() {
+        use winit::event::{ElementState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(
+            state: ElementState,
+            virtual_keycode: VirtualKeyCode,
+        ) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
+        }
+
+        let mut state = test_state();
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::A));
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::B));
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::A));
+        assert_eq!(state.pressed_keys().collect::<Vec<_>>(), [egui::Key::B]);
+
+        state.set_focused(false);
+        assert_eq!(state.pressed_keys().count(), 0);
+    }
+
+    #[test]
+    fn test_touchpad_magnify_focal_point() {
+        let pos = egui::pos2(12.0, 34.0);
+
+        let mut state = test_state();
+        state.pointer_pos_in_points = Some(pos);
+        state.on_touchpad_magnify(0.0);
+        assert_eq!(
+            state.egui_input.events,
+            [egui::Event::PointerMoved(pos), egui::Event::Zoom(1.0)]
+        );
+
+        state.egui_input.events.clear();
+        state.pointer_pos_in_points = None;
+        state.on_touchpad_magnify(0.0);
+        assert_eq!(state.egui_input.events, [egui::Event::Zoom(1.0)]);
+    }
+
+    #[test]
+    fn test_pinch_zoom() {
+        use winit::event::TouchPhase;
+
+        let mut state = test_state();
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        let touch = |state: &mut State, id, phase, x| {
+            state.egui_input.events.clear();
+            state.on_touch(&winit::event::Touch {
+                device_id,
+                phase,
+                location: winit::dpi::PhysicalPosition::new(x, 0.0),
+                force: None,
+                id,
+            });
+            state
+                .egui_input
+                .events
+                .iter()
+                .filter(|event| !matches!(event, egui::Event::Touch { .. }))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        touch(&mut state, 0, TouchPhase::Started, 0.0);
+        touch(&mut state, 1, TouchPhase::Started, 100.0);
+        assert!(touch(&mut state, 1, TouchPhase::Moved, 200.0).contains(&egui::Event::Zoom(2.0)));
+
+        // Lifting the pointer finger hands the pointer over to the other finger:
+        let events = touch(&mut state, 0, TouchPhase::Ended, 0.0);
+        assert_eq!(
+            events.last(),
+            Some(&egui::Event::PointerMoved(egui::pos2(200.0, 0.0)))
+        );
+        assert!(!events.contains(&egui::Event::PointerGone));
+        assert_eq!(
+            touch(&mut state, 1, TouchPhase::Moved, 210.0),
+            [egui::Event::PointerMoved(egui::pos2(210.0, 0.0))]
+        );
+    }
+
+    #[test]
+    fn test_turning_off_touch_simulation_cancels_touch() {
+        let mut state = test_state();
+        state.set_touch_mode(TouchMode::ForceTouch);
+        state.pointer_pos_in_points = Some(egui::pos2(1.0, 2.0));
+        state.on_pointer_button(egui::PointerButton::Primary, true);
+
+        state.egui_input.events.clear();
+        state.set_touch_mode(TouchMode::ForceMouse);
+        assert_eq!(
+            state.egui_input.events,
+            [
+                egui::Event::PointerGone,
+                egui::Event::Touch {
+                    device_id: SIMULATED_TOUCH_DEVICE_ID,
+                    id: SIMULATED_TOUCH_ID,
+                    phase: egui::TouchPhase::Cancel,
+                    pos: egui::pos2(1.0, 2.0),
+                    force: None,
+                },
+            ]
+        );
+
+        // Only once:
+        state.egui_input.events.clear();
+        state.set_touch_mode(TouchMode::ForceTouch);
+        state.set_touch_mode(TouchMode::ForceMouse);
+        assert!(state.egui_input.events.is_empty());
+    }
+
+    #[test]
+    fn test_touch_mode_auto() {
+        use winit::event::{ElementState, MouseButton};
+
+        fn touches(state: &State) -> usize {
+            state
+                .egui_input
+                .events
+                .iter()
+                .filter(|event| matches!(event, egui::Event::Touch { .. }))
+                .count()
+        }
+
+        let mut state = test_state();
+        state.set_touch_mode(TouchMode::Auto);
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+        assert_eq!(touches(&state), 2, "mouse should be simulating touches");
+
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        state.on_touch(&winit::event::Touch {
+            device_id,
+            phase: winit::event::TouchPhase::Started,
+            location: winit::dpi::PhysicalPosition::new(0.0, 0.0),
+            force: None,
+            id: 0,
+        });
+        assert_eq!(touches(&state), 3);
+
+        state.egui_input.events.clear();
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+        assert_eq!(
+            touches(&state),
+            0,
+            "real touches should stop the simulation"
+        );
+    }
+
+    #[test]
+    fn test_resize_repaint_throttle() {
+        let mut state = test_state();
+        let repaints = (0..10).filter(|_| state.should_repaint_on_resize()).count();
+        assert_eq!(repaints, 10, "no throttle by default");
+
+        state.set_resize_repaint_throttle(Some(std::time::Duration::from_secs(60)));
+        let repaints = (0..10).filter(|_| state.should_repaint_on_resize()).count();
+        assert_eq!(repaints, 1);
+    }
+
+    #[test]
+    fn test_focus_resets_attention_request() {
+        let mut state = test_state();
+        assert!(!state.on_focused(None, false));
+        assert!(!state.egui_input.focused);
+        assert!(state.on_focused(None, true));
+        assert!(state.egui_input.focused);
+    }
+
+    #[test]
+    fn test_last_input_instant() {
+        use winit::event::{ElementState, WindowEvent};
+
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+
+        let mut state = test_state();
+        let mut new_inner_size = winit::dpi::PhysicalSize::new(800, 600);
+        state.on_user_input(&WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: &mut new_inner_size,
+        });
+        #[allow(deprecated)]
+        state.on_user_input(&WindowEvent::KeyboardInput {
+            device_id,
+            input: winit::event::KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(winit::event::VirtualKeyCode::LAlt),
+                modifiers: Default::default(),
+            },
+            is_synthetic: true,
+        });
+        assert_eq!(state.last_input_instant(), None);
+
+        #[allow(deprecated)]
+        state.on_user_input(&WindowEvent::MouseInput {
+            device_id,
+            state: ElementState::Pressed,
+            button: winit::event::MouseButton::Left,
+            modifiers: Default::default(),
+        });
+        assert!(state.last_input_instant().is_some());
+    }
+
+    #[test]
+    fn test_clear_pointer_on_occlude() {
+        let mut state = test_state();
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        state.on_occluded(true);
+        assert_eq!(
+            state.pointer_pos_in_points,
+            Some(egui::Pos2::ZERO),
+            "off by default"
+        );
+        assert!(state.egui_input.events.is_empty());
+
+        state.set_clear_pointer_on_occlude(true);
+        state.on_occluded(false);
+        assert!(state.egui_input.events.is_empty());
+        state.on_occluded(true);
+        assert_eq!(state.pointer_pos_in_points, None);
+        assert_eq!(state.egui_input.events, [egui::Event::PointerGone]);
+    }
+
+    #[test]
+    fn test_focus_follows_mouse() {
+        let mut state = test_state();
+        state.set_focused(false);
+        state.on_hover_focus();
+        assert!(!state.egui_input.focused, "off by default");
+
+        state.set_focus_follows_mouse(true);
+        state.on_hover_focus();
+        assert!(state.egui_input.focused);
+    }
+
+    #[test]
+    fn test_set_start_time() {
+        let mut state = test_state();
+        let time = state.current_time();
+
+        let earlier = state.start_time() - std::time::Duration::from_secs(10);
+        state.set_start_time(earlier);
+        assert_eq!(state.start_time(), earlier);
+        assert!(state.current_time() >= time + 10.0);
+    }
+
+    #[test]
+    fn test_reset_time_origin() {
+        let mut state = test_state();
+        let uptime = std::time::Duration::from_secs(100);
+        state.set_start_time(state.start_time() - uptime);
+        assert!(state.current_time() >= 100.0);
+
+        state.reset_time_origin();
+        assert!(state.current_time() < 1.0);
+    }
+
+    #[test]
+    fn test_viewport_builder_from_info() {
+        let viewport_info = ViewportInfo {
+            title: Some("My window".to_owned()),
+            inner_rect: Some(egui::Rect::from_min_size(
+                egui::pos2(110.0, 130.0),
+                egui::vec2(800.0, 600.0),
+            )),
+            outer_rect: Some(egui::Rect::from_min_size(
+                egui::pos2(100.0, 100.0),
+                egui::vec2(820.0, 640.0),
+            )),
+            fullscreen: Some(false),
+            ..Default::default()
+        };
+
+        let builder = viewport_builder_from_info(&viewport_info);
+        assert_eq!(builder.title.as_deref(), Some("My window"));
+        assert_eq!(builder.inner_size, Some(egui::vec2(800.0, 600.0)));
+        assert_eq!(builder.position, Some(egui::pos2(100.0, 100.0)));
+        assert_eq!(builder.fullscreen, Some(false));
+        assert_eq!(builder.maximized, None);
+    }
+
+    #[test]
+    fn test_simulated_touch_ids_are_distinct() {
+        use winit::event::{ElementState, MouseButton};
+
+        fn touch_ids(state: &State) -> Vec<(egui::TouchDeviceId, egui::TouchId)> {
+            state
+                .egui_input
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Touch { device_id, id, .. } => Some((*device_id, *id)),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        let mut state = test_state();
+        state.set_touch_mode(TouchMode::ForceTouch);
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+        let simulated = touch_ids(&state);
+        assert_eq!(simulated, [(SIMULATED_TOUCH_DEVICE_ID, SIMULATED_TOUCH_ID)]);
+
+        state.egui_input.events.clear();
+
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        state.on_touch(&winit::event::Touch {
+            device_id,
+            phase: winit::event::TouchPhase::Started,
+            location: winit::dpi::PhysicalPosition::new(0.0, 0.0),
+            force: None,
+            id: 0,
+        });
+        let ids = touch_ids(&state);
+        let (real, simulated_from_real) = ids.split_first().unwrap();
+        assert_ne!(real.0, SIMULATED_TOUCH_DEVICE_ID);
+        assert_ne!(real.1, SIMULATED_TOUCH_ID);
+        assert!(simulated_from_real
+            .iter()
+            .all(|&ids| ids == (SIMULATED_TOUCH_DEVICE_ID, SIMULATED_TOUCH_ID)));
+    }
+
+    #[test]
+    fn test_defer_url_open() {
+        let mut state = test_state();
+        state.set_defer_url_open(true);
+
+        let url = egui::output::OpenUrl::new_tab("https://www.egui.rs");
+        assert_eq!(state.open_url(url.clone()), None);
+        assert!(state.take_pending_url() == Some(url));
+        assert!(state.take_pending_url().is_none());
+    }
+
+    #[test]
+    fn test_system_theme() {
+        let mut state = test_state();
+        state.egui_ctx().set_visuals(egui::Visuals::light());
+        assert_eq!(state.system_theme(), None);
+
+        state.on_theme_changed(winit::window::Theme::Dark);
+        assert_eq!(state.system_theme(), Some(egui::SystemTheme::Dark));
+        assert!(
+            !state.egui_ctx().style().visuals.dark_mode,
+            "visuals are left alone by default"
+        );
+
+        state.set_follow_system_theme(true);
+        assert!(state.egui_ctx().style().visuals.dark_mode);
+
+        state.on_theme_changed(winit::window::Theme::Light);
+        assert_eq!(state.system_theme(), Some(egui::SystemTheme::Light));
+        assert!(!state.egui_ctx().style().visuals.dark_mode);
+    }
+
+    #[test]
+    fn test_open_url_handler() {
+        let opened = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+
+        let mut state = test_state();
+        state.set_open_url_handler({
+            let opened = opened.clone();
+            move |open_url| opened.lock().push((open_url.url.clone(), open_url.new_tab))
+        });
+
+        let url = egui::OpenUrl::new_tab("https://www.egui.rs");
+        assert_eq!(state.open_url(url), Some("https://www.egui.rs".to_owned()));
+        assert_eq!(*opened.lock(), [("https://www.egui.rs".to_owned(), true)]);
+    }
+
+    #[test]
+    fn test_open_url_handler_same_tab() {
+        let opened = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+
+        let mut state = test_state();
+        state.set_open_url_handler({
+            let opened = opened.clone();
+            move |open_url| opened.lock().push((open_url.url.clone(), open_url.new_tab))
+        });
+
+        let url = egui::OpenUrl::same_tab("https://docs.rs/egui");
+        assert_eq!(state.open_url(url), Some("https://docs.rs/egui".to_owned()));
+        assert_eq!(
+            *opened.lock(),
+            [("https://docs.rs/egui".to_owned(), false)],
+            "`new_tab` should be passed on, not dropped"
+        );
+    }
+
+    #[test]
+    fn test_smart_magnify() {
+        let mut state = test_state();
+        assert!(!state.on_smart_magnify(), "opt-in");
+        assert!(state.egui_input.events.is_empty());
+
+        state.set_smart_magnify_event(Some(egui::Event::Zoom(1.0)));
+        assert!(state.on_smart_magnify());
+        assert_eq!(state.egui_input.events, [egui::Event::Zoom(1.0)]);
+    }
+
+    #[test]
+    fn test_rotation_delta() {
+        let mut state = test_state();
+        state.on_touchpad_rotate(30.0);
+        state.on_touchpad_rotate(60.0);
+        assert_eq!(state.take_rotation_delta(), 0.0, "not until the next frame");
+
+        state.latch_trackpad_gestures();
+        assert_eq!(state.take_rotation_delta(), std::f32::consts::FRAC_PI_2);
+        assert_eq!(state.take_rotation_delta(), 0.0);
+
+        // Rotation not taken during a frame is dropped:
+        state.on_touchpad_rotate(45.0);
+        state.latch_trackpad_gestures();
+        state.latch_trackpad_gestures();
+        assert_eq!(state.take_rotation_delta(), 0.0);
+    }
+
+    #[test]
+    fn test_take_touchpad_pressure() {
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+
+        let mut state = test_state();
+        assert!(
+            !state.on_touchpad_pressure(device_id, 0.3, 1),
+            "force touch is off"
+        );
+        assert!(state.egui_input.events.is_empty());
+        state.on_touchpad_pressure(device_id, 0.9, 2);
+        assert_eq!(
+            state.take_touchpad_pressure(),
+            None,
+            "not until the next frame"
+        );
+
+        state.latch_trackpad_gestures();
+        assert_eq!(
+            state.take_touchpad_pressure(),
+            Some((0.9, 2)),
+            "latest wins"
+        );
+        assert_eq!(state.take_touchpad_pressure(), None);
+
+        state.latch_trackpad_gestures();
+        assert_eq!(state.take_touchpad_pressure(), None, "nothing new");
+    }
+
+    #[test]
+    fn test_take_raw_input_reserves_events() {
+        let mut state = test_state();
+        let num_events = 1000;
+        for i in 0..num_events {
+            state.on_pointer_moved(egui::pos2(i as f32, 0.0));
+        }
+        assert_eq!(state.take_raw_input().events.len(), num_events);
+
+        // The next frame of the same input shouldn't need to grow the `Vec`:
+        let capacity = state.egui_input.events.capacity();
+        assert!(num_events <= capacity);
+        for i in 0..num_events {
+            state.on_pointer_moved(egui::pos2(i as f32, 0.0));
+        }
+        assert_eq!(state.egui_input.events.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_raw_mouse_delta() {
+        use winit::event::DeviceEvent;
+
+        let mut state = test_state();
+        let response = state.on_device_event(&DeviceEvent::MouseMotion { delta: (3.0, -1.0) });
+        assert!(
+            !response.repaint,
+            "motion alone should not repaint, e.g. in the background"
+        );
+        assert!(!response.consumed);
+        assert_eq!(state.last_input_instant, None);
+        let _ = state.on_device_event(&DeviceEvent::MouseMotion { delta: (2.0, 4.0) });
+        let _ = state.on_device_event(&DeviceEvent::Added);
+
+        assert_eq!(state.take_raw_mouse_delta(), egui::vec2(5.0, 3.0));
+        assert_eq!(state.take_raw_mouse_delta(), egui::Vec2::ZERO);
+        assert!(state.egui_input.events.is_empty());
+    }
+
+    #[test]
+    fn test_trust_egui_repaint() {
+        let event = winit::event::WindowEvent::Focused(true);
+        let repaint = EventResponse {
+            repaint: true,
+            ..Default::default()
+        };
+
+        let mut state = test_state();
+        assert!(state.finish_response(&event, repaint).repaint);
+        assert!(state.on_remote_event(RemoteEvent::Focused(true)).repaint);
+
+        state.set_trust_egui_repaint(true);
+        assert!(!state.finish_response(&event, repaint).repaint);
+        assert!(!state.on_remote_event(RemoteEvent::Focused(true)).repaint);
+    }
+
+    #[test]
+    fn test_dedup_cursor_sets() {
+        let mut state = test_state();
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        let sets = |state: &mut State| {
+            (0..3)
+                .filter(|_| state.set_cursor_icon(None, egui::CursorIcon::Text))
+                .count()
+        };
+        assert_eq!(sets(&mut state), 1);
+
+        state.set_dedup_cursor_sets(false);
+        assert_eq!(sets(&mut state), 3, "every request should set the cursor");
+    }
+
+    #[test]
+    fn test_cursor_change_callback() {
+        use egui::CursorIcon;
+
+        let changes = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+
+        let mut state = test_state();
+        state.set_dedup_cursor_sets(false);
+        state.set_cursor_change_callback({
+            let changes = changes.clone();
+            move |icon| changes.lock().push(icon)
+        });
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+
+        for icon in [
+            CursorIcon::Default,
+            CursorIcon::Default,
+            CursorIcon::Text,
+            CursorIcon::Text,
+            CursorIcon::Default,
+        ] {
+            state.update_cursor_icon(icon);
+        }
+        assert_eq!(
+            *changes.lock(),
+            [CursorIcon::Default, CursorIcon::Text, CursorIcon::Default]
+        );
+
+        // Leave and re-enter the window with the same icon:
+        state.pointer_pos_in_points = None;
+        assert!(!state.update_cursor_icon(CursorIcon::Default));
+        state.pointer_pos_in_points = Some(egui::Pos2::ZERO);
+        assert!(state.update_cursor_icon(CursorIcon::Default));
+        assert_eq!(
+            *changes.lock(),
+            [CursorIcon::Default, CursorIcon::Text, CursorIcon::Default],
+            "the same icon should not be reported again"
+        );
+    }
 
-    if let Some(size) = inner_size {
-        window_builder = window_builder.with_inner_size(PhysicalSize::new(
-            pixels_per_point * size.x,
-            pixels_per_point * size.y,
-        ));
+    #[test]
+    fn test_key_repeat_acceleration() {
+        use std::time::Duration;
+
+        let mut state = test_state();
+        state.set_key_repeat_acceleration(Some(AccelCurve {
+            initial_points: 10.0,
+            points_per_second: 20.0,
+            max_points: 100.0,
+        }));
+
+        let start = web_time::Instant::now();
+        let key = egui::Key::ArrowDown;
+        state.accelerate_key_repeat(key, true, false, start);
+        for secs in [1, 2, 10] {
+            let now = start + Duration::from_secs(secs);
+            state.accelerate_key_repeat(key, true, true, now);
+        }
+        state.accelerate_key_repeat(key, false, false, start + Duration::from_secs(11));
+
+        let deltas: Vec<f32> = state
+            .egui_input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Scroll(delta) => Some(delta.y),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, [-30.0, -50.0, -100.0]);
+        assert_eq!(state.key_hold_start, None);
+
+        // No scrolling while a text field has keyboard focus:
+        run_text_edit(&mut state, &mut String::new());
+        assert!(state.egui_ctx().wants_keyboard_input());
+        state.accelerate_key_repeat(key, true, false, start);
+        state.accelerate_key_repeat(key, true, true, start + Duration::from_secs(1));
+        assert!(!state
+            .egui_input
+            .events
+            .iter()
+            .any(|event| matches!(event, egui::Event::Scroll(_))));
     }
 
-    if let Some(size) = min_inner_size {
-        window_builder = window_builder.with_min_inner_size(PhysicalSize::new(
-            pixels_per_point * size.x,
-            pixels_per_point * size.y,
-        ));
+    #[test]
+    fn test_consume_reason() {
+        use winit::event::{ElementState, MouseButton, VirtualKeyCode, WindowEvent};
+
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+
+        let state = test_state();
+        let ctx = state.egui_ctx().clone();
+
+        // Hover a panel, so that egui wants the pointer:
+        for _ in 0..2 {
+            let input = egui::RawInput {
+                events: vec![egui::Event::PointerMoved(egui::pos2(10.0, 10.0))],
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |_ui| {});
+            });
+        }
+        assert!(ctx.wants_pointer_input());
+
+        #[allow(deprecated)]
+        let click = WindowEvent::MouseInput {
+            device_id,
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        };
+        assert_eq!(
+            state.consume_reason(&click),
+            Some(ConsumeReason::PointerOverArea)
+        );
+        assert_eq!(
+            state.consume_reason(&WindowEvent::SmartMagnify { device_id }),
+            Some(ConsumeReason::PointerOverArea)
+        );
+
+        #[allow(deprecated)]
+        let tab = WindowEvent::KeyboardInput {
+            device_id,
+            input: winit::event::KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::Tab),
+                modifiers: Default::default(),
+            },
+            is_synthetic: false,
+        };
+        assert_eq!(
+            state.consume_reason(&tab),
+            Some(ConsumeReason::TabTraversal)
+        );
     }
 
-    if let Some(size) = max_inner_size {
-        window_builder = window_builder.with_max_inner_size(PhysicalSize::new(
-            pixels_per_point * size.x,
-            pixels_per_point * size.y,
-        ));
+    #[test]
+    fn test_consumed_pointer_vs_keyboard() {
+        let mut state = test_state();
+        let ctx = state.egui_ctx().clone();
+
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.text_edit_singleline(&mut String::new()).request_focus();
+            });
+        });
+        assert!(ctx.wants_keyboard_input());
+
+        let key = state.on_remote_event(RemoteEvent::Key {
+            key: egui::Key::A,
+            pressed: true,
+        });
+        assert!(key.consumed);
+        assert!(key.consumed_keyboard());
+        assert!(!key.consumed_pointer());
+
+        // The pointer is free for e.g. camera control:
+        let pointer = state.on_remote_event(RemoteEvent::PointerMoved(egui::pos2(1.0, 2.0)));
+        assert!(!pointer.consumed);
+        assert!(!pointer.consumed_pointer());
+        assert!(!pointer.consumed_keyboard());
     }
 
-    if let Some(pos) = position {
-        window_builder = window_builder.with_position(PhysicalPosition::new(
-            pixels_per_point * pos.x,
-            pixels_per_point * pos.y,
-        ));
+    #[test]
+    fn test_focus_changed() {
+        let mut state = test_state();
+        assert_eq!(
+            state
+                .on_remote_event(RemoteEvent::Focused(false))
+                .focus_changed,
+            Some(false)
+        );
+        assert_eq!(
+            state
+                .on_remote_event(RemoteEvent::Focused(true))
+                .focus_changed,
+            Some(true)
+        );
+        assert_eq!(
+            state
+                .on_remote_event(RemoteEvent::PointerMoved(egui::Pos2::ZERO))
+                .focus_changed,
+            None
+        );
     }
 
-    if let Some(icon) = icon {
-        window_builder = window_builder.with_window_icon(Some(
-            winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
-                .expect("Invalid Icon Data!"),
-        ));
+    #[test]
+    fn test_drag_sticky() {
+        use winit::event::{ElementState, MouseButton};
+
+        let pos = egui::pos2(10.0, 20.0);
+
+        let mut state = test_state();
+        state.set_drag_sticky(true);
+        state.on_pointer_moved(pos);
+        state.on_mouse_button_input(ElementState::Pressed, MouseButton::Left);
+
+        state.on_cursor_left();
+        assert_eq!(state.pointer_pos_in_points, Some(pos));
+        assert!(!state.egui_input.events.contains(&egui::Event::PointerGone));
+
+        // Released outside the window:
+        state.on_mouse_button_input(ElementState::Released, MouseButton::Left);
+        assert_eq!(state.pointer_pos_in_points, None);
+        assert_eq!(
+            state.egui_input.events.last(),
+            Some(&egui::Event::PointerGone)
+        );
     }
 
-    #[cfg(all(feature = "wayland", target_os = "linux"))]
-    if let Some(app_id) = _app_id {
-        use winit::platform::wayland::WindowBuilderExtWayland as _;
-        window_builder = window_builder.with_name(app_id, "");
+    #[test]
+    fn test_round_pointer_to_pixels() {
+        let pixels_per_point = 1.5;
+        let pos_in_pixels = winit::dpi::PhysicalPosition::new(10.4, 20.6);
+
+        let exact = pointer_pos_in_points(pos_in_pixels, pixels_per_point, false);
+        assert_eq!(exact, egui::pos2(10.4, 20.6) / pixels_per_point);
+
+        let rounded = pointer_pos_in_points(pos_in_pixels, pixels_per_point, true);
+        assert_eq!(rounded * pixels_per_point, egui::pos2(10.0, 21.0));
+        assert_eq!(
+            pointer_pos_in_points(
+                winit::dpi::PhysicalPosition::new(9.6, 21.4),
+                pixels_per_point,
+                true
+            ),
+            rounded
+        );
     }
 
-    #[cfg(target_os = "windows")]
-    if let Some(enable) = _drag_and_drop {
-        use winit::platform::windows::WindowBuilderExtWindows as _;
-        window_builder = window_builder.with_drag_and_drop(enable);
+    #[test]
+    fn test_ime_commit_resets_modifiers() {
+        use winit::event::{Ime, ModifiersState};
+
+        let mut state = test_state();
+
+        // Shift is held when composing starts, and released while the IME has focus:
+        state.on_modifiers_changed(ModifiersState::SHIFT);
+        state.on_ime(&Ime::Preedit("A".to_owned(), Some((1, 1))));
+        state.on_ime(&Ime::Commit("A".to_owned()));
+        assert_eq!(state.modifiers(), egui::Modifiers::NONE);
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        use winit::platform::macos::WindowBuilderExtMacOS as _;
-        window_builder = window_builder
-            .with_title_hidden(!_title_shown.unwrap_or(true))
-            .with_titlebar_buttons_hidden(!_titlebar_buttons_shown.unwrap_or(true))
-            .with_titlebar_transparent(!_titlebar_shown.unwrap_or(true))
-            .with_fullsize_content_view(_fullsize_content_view.unwrap_or(false));
+    #[test]
+    fn test_modifiers() {
+        use winit::event::ModifiersState;
+
+        let mut state = test_state();
+        assert_eq!(state.modifiers(), egui::Modifiers::NONE);
+
+        state.on_modifiers_changed(ModifiersState::SHIFT);
+        assert!(state.modifiers().shift);
+
+        state.on_modifiers_changed(ModifiersState::empty());
+        assert!(!state.modifiers().shift);
     }
 
-    window_builder
-}
+    #[test]
+    fn test_undo_redo_keys_edit_text() {
+        use winit::event::{ElementState, ModifiersState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(
+            state: ElementState,
+            virtual_keycode: VirtualKeyCode,
+        ) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
+        }
 
-/// Applies what `create_winit_window_builder` couldn't
-pub fn apply_viewport_builder_to_window(
-    egui_ctx: &egui::Context,
-    window: &Window,
-    builder: &ViewportBuilder,
-) {
-    if let Some(mouse_passthrough) = builder.mouse_passthrough {
-        if let Err(err) = window.set_cursor_hittest(!mouse_passthrough) {
-            log::warn!("set_cursor_hittest failed: {err}");
+        fn press(state: &mut State, modifiers: ModifiersState, keycode: VirtualKeyCode) {
+            state.on_modifiers_changed(modifiers);
+            state.on_keyboard_input(&key(ElementState::Pressed, keycode));
+            state.on_keyboard_input(&key(ElementState::Released, keycode));
+            state.on_modifiers_changed(ModifiersState::empty());
+        }
+
+        for (style, undo, redo) in [
+            (
+                TextNavStyle::Mac,
+                (ModifiersState::LOGO, VirtualKeyCode::Z),
+                (
+                    ModifiersState::LOGO | ModifiersState::SHIFT,
+                    VirtualKeyCode::Z,
+                ),
+            ),
+            (
+                TextNavStyle::Windows,
+                (ModifiersState::ALT, VirtualKeyCode::Back),
+                (ModifiersState::CTRL, VirtualKeyCode::Y),
+            ),
+        ] {
+            let mut state = test_state();
+            state.set_text_nav_style(style);
+            let mut text = String::new();
+            run_text_edit(&mut state, &mut text);
+            state
+                .egui_input
+                .events
+                .push(egui::Event::Text("hello".to_owned()));
+            run_text_edit(&mut state, &mut text);
+
+            press(&mut state, undo.0, undo.1);
+            run_text_edit(&mut state, &mut text);
+            assert_eq!(text, "", "{style:?} undo");
+            assert_eq!(state.egui_ctx().input(|i| i.keys_down.len()), 0);
+
+            press(&mut state, redo.0, redo.1);
+            run_text_edit(&mut state, &mut text);
+            assert_eq!(text, "hello", "{style:?} redo");
+            assert_eq!(state.egui_ctx().input(|i| i.keys_down.len()), 0);
         }
     }
 
-    {
-        // In `create_winit_window_builder` we didn't know
-        // on what monitor the window would appear, so we didn't know
-        // how to translate egui ui point to native physical pixels.
-        // Now we do know:
+    #[test]
+    fn test_undo_redo_commands() {
+        use egui::Modifiers;
+        use winit::event::{ModifiersState, VirtualKeyCode};
 
-        let pixels_per_point = pixels_per_point(egui_ctx, window);
+        let ctrl = translate_modifiers(ModifiersState::CTRL, TextNavStyle::Windows);
+        let ctrl_shift = translate_modifiers(
+            ModifiersState::CTRL | ModifiersState::SHIFT,
+            TextNavStyle::Windows,
+        );
+        assert!(is_undo_command(
+            TextNavStyle::Windows,
+            ctrl,
+            VirtualKeyCode::Z
+        ));
+        assert!(!is_redo_command(
+            TextNavStyle::Windows,
+            ctrl,
+            VirtualKeyCode::Z
+        ));
+        assert!(is_redo_command(
+            TextNavStyle::Windows,
+            ctrl,
+            VirtualKeyCode::Y
+        ));
+        assert!(is_redo_command(
+            TextNavStyle::Windows,
+            ctrl_shift,
+            VirtualKeyCode::Z
+        ));
+        assert!(!is_undo_command(
+            TextNavStyle::Windows,
+            ctrl_shift,
+            VirtualKeyCode::Z
+        ));
+        assert!(is_undo_command(
+            TextNavStyle::Windows,
+            Modifiers::ALT,
+            VirtualKeyCode::Back
+        ));
 
-        if let Some(size) = builder.inner_size {
-            window.set_inner_size(PhysicalSize::new(
-                pixels_per_point * size.x,
-                pixels_per_point * size.y,
-            ));
+        let cmd = translate_modifiers(ModifiersState::LOGO, TextNavStyle::Mac);
+        let cmd_shift = translate_modifiers(
+            ModifiersState::LOGO | ModifiersState::SHIFT,
+            TextNavStyle::Mac,
+        );
+        assert!(is_undo_command(TextNavStyle::Mac, cmd, VirtualKeyCode::Z));
+        assert!(is_redo_command(
+            TextNavStyle::Mac,
+            cmd_shift,
+            VirtualKeyCode::Z
+        ));
+        assert!(!is_redo_command(TextNavStyle::Mac, cmd, VirtualKeyCode::Y));
+        assert!(!is_undo_command(
+            TextNavStyle::Mac,
+            Modifiers::ALT,
+            VirtualKeyCode::Back
+        ));
+    }
+
+    #[test]
+    fn test_keyboard_capture() {
+        use winit::event::{ElementState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(virtual_keycode: VirtualKeyCode) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
         }
-        if let Some(size) = builder.min_inner_size {
-            window.set_min_inner_size(Some(PhysicalSize::new(
-                pixels_per_point * size.x,
-                pixels_per_point * size.y,
-            )));
+
+        let captured = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+
+        let mut state = test_state();
+        state.set_keyboard_capture({
+            let captured = captured.clone();
+            move |input| {
+                captured.lock().push(input.virtual_keycode);
+                true
+            }
+        });
+        assert!(state.on_keyboard_input(&key(VirtualKeyCode::Escape)));
+        assert_eq!(*captured.lock(), [Some(VirtualKeyCode::Escape)]);
+        assert!(state.egui_input.events.is_empty());
+
+        state.clear_keyboard_capture();
+        assert!(!state.on_keyboard_input(&key(VirtualKeyCode::Escape)));
+        assert_eq!(captured.lock().len(), 1);
+        assert_eq!(state.egui_input.events.len(), 1);
+    }
+
+    #[test]
+    fn test_raw_keycode_sink() {
+        use winit::event::{ElementState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(
+            state: ElementState,
+            virtual_keycode: VirtualKeyCode,
+        ) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
         }
-        if let Some(size) = builder.max_inner_size {
-            window.set_max_inner_size(Some(PhysicalSize::new(
-                pixels_per_point * size.x,
-                pixels_per_point * size.y,
-            )));
+
+        let received = std::sync::Arc::new(egui::mutex::Mutex::new(Vec::new()));
+
+        let mut state = test_state();
+        state.set_raw_keycode_sink({
+            let received = received.clone();
+            move |keycode, pressed| received.lock().push((keycode, pressed))
+        });
+        for keycode in [VirtualKeyCode::PlayPause, VirtualKeyCode::A] {
+            for element_state in [ElementState::Pressed, ElementState::Released] {
+                assert!(!state.on_keyboard_input(&key(element_state, keycode)));
+            }
         }
-        if let Some(pos) = builder.position {
-            let pos = PhysicalPosition::new(pixels_per_point * pos.x, pixels_per_point * pos.y);
-            window.set_outer_position(pos);
+        assert_eq!(
+            *received.lock(),
+            [
+                (VirtualKeyCode::PlayPause, true),
+                (VirtualKeyCode::PlayPause, false)
+            ],
+            "mapped keys go to egui only"
+        );
+        assert_eq!(state.egui_input.events.len(), 2);
+
+        state.clear_raw_keycode_sink();
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::VolumeUp));
+        assert_eq!(received.lock().len(), 2);
+    }
+
+    #[test]
+    fn test_dead_key_sequence() {
+        use winit::event::{ElementState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(
+            state: ElementState,
+            virtual_keycode: VirtualKeyCode,
+        ) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
         }
+
+        let mut state = test_state();
+        state.set_suppress_dead_keys(true);
+
+        // Compose, ', e → é
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::Compose));
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::Compose));
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::Apostrophe));
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::Apostrophe));
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::E));
+        state.on_received_character('é');
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::E));
+        assert_eq!(state.egui_input.events, [egui::Event::Text("é".to_owned())]);
+        assert_eq!(state.pressed_keys().count(), 0);
+
+        // Back to normal:
+        state.egui_input.events.clear();
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::E));
+        assert_eq!(state.pressed_keys().collect::<Vec<_>>(), [egui::Key::E]);
     }
-}
 
-// ---------------------------------------------------------------------------
+    #[test]
+    fn test_aborted_dead_key_sequence() {
+        use winit::event::{ElementState, VirtualKeyCode};
+
+        #[allow(deprecated)]
+        fn key(
+            state: ElementState,
+            virtual_keycode: VirtualKeyCode,
+        ) -> winit::event::KeyboardInput {
+            winit::event::KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(virtual_keycode),
+                modifiers: Default::default(),
+            }
+        }
 
-/// Short and fast description of an event.
-/// Useful for logging and profiling.
-pub fn short_generic_event_description<T>(event: &winit::event::Event<'_, T>) -> &'static str {
-    use winit::event::{DeviceEvent, Event, StartCause};
+        let mut state = test_state();
+        state.set_suppress_dead_keys(true);
+
+        // Compose, then an arrow key, which types no character:
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::Compose));
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::Compose));
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::Left));
+        state.on_keyboard_input(&key(ElementState::Released, VirtualKeyCode::Left));
+        assert_eq!(
+            state.egui_input.events.len(),
+            2,
+            "the arrow key goes to egui"
+        );
 
-    match event {
-        Event::Suspended => "Event::Suspended",
-        Event::Resumed => "Event::Resumed",
-        Event::MainEventsCleared => "Event::MainEventsCleared",
-        Event::RedrawRequested(_) => "Event::RedrawRequested",
-        Event::RedrawEventsCleared => "Event::RedrawEventsCleared",
-        Event::LoopDestroyed => "Event::LoopDestroyed",
-        Event::UserEvent(_) => "UserEvent",
-        Event::DeviceEvent { event, .. } => match event {
-            DeviceEvent::Added { .. } => "DeviceEvent::Added",
-            DeviceEvent::Removed { .. } => "DeviceEvent::Removed",
-            DeviceEvent::MouseMotion { .. } => "DeviceEvent::MouseMotion",
-            DeviceEvent::MouseWheel { .. } => "DeviceEvent::MouseWheel",
-            DeviceEvent::Motion { .. } => "DeviceEvent::Motion",
-            DeviceEvent::Button { .. } => "DeviceEvent::Button",
-            DeviceEvent::Key { .. } => "DeviceEvent::Key",
-            DeviceEvent::Text { .. } => "DeviceEvent::Text",
-        },
-        Event::NewEvents(start_cause) => match start_cause {
-            StartCause::ResumeTimeReached { .. } => "NewEvents::ResumeTimeReached",
-            StartCause::WaitCancelled { .. } => "NewEvents::WaitCancelled",
-            StartCause::Poll => "NewEvents::Poll",
-            StartCause::Init => "NewEvents::Init",
-        },
-        Event::WindowEvent { event, .. } => short_window_event_description(event),
+        // The sequence is over, so later keys are not swallowed:
+        state.egui_input.events.clear();
+        state.on_keyboard_input(&key(ElementState::Pressed, VirtualKeyCode::E));
+        assert_eq!(state.pressed_keys().collect::<Vec<_>>(), [egui::Key::E]);
     }
-}
 
-/// Short and fast description of an event.
-/// Useful for logging and profiling.
-pub fn short_window_event_description(event: &winit::event::WindowEvent<'_>) -> &'static str {
-    use winit::event::WindowEvent;
+    #[test]
+    fn test_describe_window_event() {
+        use winit::event::{ElementState, MouseButton, WindowEvent};
 
-    match event {
-        WindowEvent::Resized { .. } => "WindowEvent::Resized",
-        WindowEvent::Moved { .. } => "WindowEvent::Moved",
-        WindowEvent::CloseRequested { .. } => "WindowEvent::CloseRequested",
-        WindowEvent::Destroyed { .. } => "WindowEvent::Destroyed",
-        WindowEvent::DroppedFile { .. } => "WindowEvent::DroppedFile",
-        WindowEvent::HoveredFile { .. } => "WindowEvent::HoveredFile",
-        WindowEvent::HoveredFileCancelled { .. } => "WindowEvent::HoveredFileCancelled",
-        WindowEvent::ReceivedCharacter { .. } => "WindowEvent::ReceivedCharacter",
-        WindowEvent::Focused { .. } => "WindowEvent::Focused",
-        WindowEvent::KeyboardInput { .. } => "WindowEvent::KeyboardInput",
-        WindowEvent::ModifiersChanged { .. } => "WindowEvent::ModifiersChanged",
-        WindowEvent::Ime { .. } => "WindowEvent::Ime",
-        WindowEvent::CursorMoved { .. } => "WindowEvent::CursorMoved",
-        WindowEvent::CursorEntered { .. } => "WindowEvent::CursorEntered",
-        WindowEvent::CursorLeft { .. } => "WindowEvent::CursorLeft",
-        WindowEvent::MouseWheel { .. } => "WindowEvent::MouseWheel",
-        WindowEvent::MouseInput { .. } => "WindowEvent::MouseInput",
-        WindowEvent::TouchpadMagnify { .. } => "WindowEvent::TouchpadMagnify",
-        WindowEvent::SmartMagnify { .. } => "WindowEvent::SmartMagnify",
-        WindowEvent::TouchpadRotate { .. } => "WindowEvent::TouchpadRotate",
-        WindowEvent::TouchpadPressure { .. } => "WindowEvent::TouchpadPressure",
-        WindowEvent::AxisMotion { .. } => "WindowEvent::AxisMotion",
-        WindowEvent::Touch { .. } => "WindowEvent::Touch",
-        WindowEvent::ScaleFactorChanged { .. } => "WindowEvent::ScaleFactorChanged",
-        WindowEvent::ThemeChanged { .. } => "WindowEvent::ThemeChanged",
-        WindowEvent::Occluded { .. } => "WindowEvent::Occluded",
+        // SAFETY: only used for testing, and never passed to winit.
+        #[allow(unsafe_code)]
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+
+        #[allow(deprecated)]
+        let mouse_input = WindowEvent::MouseInput {
+            device_id,
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        };
+        assert_eq!(
+            describe_window_event(&mouse_input),
+            "MouseInput(Left, Pressed)"
+        );
+
+        assert_eq!(
+            describe_window_event(&WindowEvent::Resized(winit::dpi::PhysicalSize::new(
+                800, 600
+            ))),
+            "Resized(800x600)"
+        );
+        assert_eq!(
+            describe_window_event(&WindowEvent::Focused(true)),
+            "Focused(true)"
+        );
+        assert_eq!(
+            describe_window_event(&WindowEvent::CloseRequested),
+            "CloseRequested"
+        );
     }
-}
 
-// ---------------------------------------------------------------------------
+    #[test]
+    fn test_clear_hovered_files() {
+        let mut state = test_state();
+        state.egui_input.hovered_files.push(Default::default());
+        assert_eq!(state.hovered_files().len(), 1);
 
-mod profiling_scopes {
-    #![allow(unused_macros)]
-    #![allow(unused_imports)]
+        state.clear_hovered_files();
+        assert!(state.hovered_files().is_empty());
 
-    /// Profiling macro for feature "puffin"
-    macro_rules! profile_function {
-        ($($arg: tt)*) => {
-            #[cfg(feature = "puffin")]
-            #[cfg(not(target_arch = "wasm32"))] // Disabled on web because of the coarse 1ms clock resolution there.
-            puffin::profile_function!($($arg)*);
-        };
+        state.egui_input.hovered_files.push(Default::default());
+        state.set_focused(false);
+        assert!(state.hovered_files().is_empty());
     }
-    pub(crate) use profile_function;
 
-    /// Profiling macro for feature "puffin"
-    macro_rules! profile_scope {
-        ($($arg: tt)*) => {
-            #[cfg(feature = "puffin")]
-            #[cfg(not(target_arch = "wasm32"))] // Disabled on web because of the coarse 1ms clock resolution there.
-            puffin::profile_scope!($($arg)*);
-        };
+    #[test]
+    fn test_force_click() {
+        assert!(is_force_click(1, 2));
+        assert!(is_force_click(0, 2));
+        assert!(
+            !is_force_click(2, 2),
+            "Holding a force click should not repeat it"
+        );
+        assert!(!is_force_click(0, 1));
+        assert!(!is_force_click(2, 1));
+    }
+
+    #[test]
+    fn test_key_name() {
+        use winit::event::VirtualKeyCode;
+
+        assert_eq!(key_name(VirtualKeyCode::A), Some("A"));
+        assert_eq!(key_name(VirtualKeyCode::Numpad7), Some("7"));
+        assert_eq!(key_name(VirtualKeyCode::Return), Some("Enter"));
+        assert_eq!(key_name(VirtualKeyCode::F12), Some("F12"));
+        assert_eq!(key_name(VirtualKeyCode::Capital), None);
+    }
+
+    #[test]
+    fn test_translate_punctuation_keys() {
+        use egui::Key;
+        use winit::event::VirtualKeyCode;
+
+        for (keycode, key) in [
+            (VirtualKeyCode::Minus, Key::Minus),
+            (VirtualKeyCode::NumpadSubtract, Key::Minus),
+            (VirtualKeyCode::Equals, Key::PlusEquals),
+            (VirtualKeyCode::Plus, Key::PlusEquals),
+            (VirtualKeyCode::NumpadAdd, Key::PlusEquals),
+            (VirtualKeyCode::NumpadEquals, Key::PlusEquals),
+        ] {
+            assert_eq!(
+                translate_virtual_key_code(keycode),
+                Some(key),
+                "{keycode:?}"
+            );
+        }
+
+        for keycode in [
+            VirtualKeyCode::Comma,
+            VirtualKeyCode::Period,
+            VirtualKeyCode::Semicolon,
+            VirtualKeyCode::Slash,
+            VirtualKeyCode::Backslash,
+            VirtualKeyCode::LBracket,
+            VirtualKeyCode::RBracket,
+            VirtualKeyCode::Apostrophe,
+            VirtualKeyCode::Grave,
+        ] {
+            assert_eq!(translate_virtual_key_code(keycode), None, "{keycode:?}");
+        }
     }
-    pub(crate) use profile_scope;
 }
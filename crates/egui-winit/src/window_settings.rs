@@ -86,6 +86,44 @@ impl WindowSettings {
         }
     }
 
+    /// Settings for a new window, placed diagonally below and to the right of the `previous` one,
+    /// so that a series of new windows cascade across the monitor.
+    ///
+    /// The first window (`previous == None`) is placed near the top-left corner of the monitor.
+    /// If the new window would not be within the monitor, e.g. because it runs off the edge
+    /// or `previous` is on another monitor, the cascade starts over from there.
+    /// The size is copied from the `previous` window.
+    pub fn cascade_from(previous: Option<&Self>, monitor: &MonitorInfo) -> Self {
+        let offset_px = egui::Vec2::splat(CASCADE_OFFSET_POINTS * monitor.scale_factor);
+        let first_pos_px = monitor.position_px + offset_px;
+
+        let inner_size_points = previous.and_then(|previous| previous.inner_size_points);
+        let previous_pos_px = previous.and_then(|previous| {
+            previous
+                .outer_position_pixels
+                .or(previous.inner_position_pixels)
+        });
+
+        let mut pos_px = previous_pos_px.map_or(first_pos_px, |pos| pos + offset_px);
+
+        let size_px = inner_size_points.unwrap_or_default() * monitor.scale_factor;
+        let monitor_max_px = monitor.position_px + monitor.size_px;
+        if pos_px.x < monitor.position_px.x
+            || pos_px.y < monitor.position_px.y
+            || monitor_max_px.x < pos_px.x + size_px.x
+            || monitor_max_px.y < pos_px.y + size_px.y
+        {
+            pos_px = first_pos_px;
+        }
+
+        Self {
+            inner_position_pixels: Some(pos_px),
+            outer_position_pixels: Some(pos_px),
+            fullscreen: false,
+            inner_size_points,
+        }
+    }
+
     pub fn clamp_size_to_sane_values(&mut self, largest_monitor_size_points: egui::Vec2) {
         use egui::NumExt as _;
 
@@ -125,6 +163,125 @@ impl WindowSettings {
             clamp_pos_to_monitors(egui_zoom_factor, event_loop, inner_size_points, pos_px);
         }
     }
+
+    /// Move the window onto the nearest of the given monitors, unless its top-left corner
+    /// is already on one of them, so the user can always reach the title bar.
+    ///
+    /// Call this before [`Self::initialize_viewport_builder`], so that a window that was last
+    /// on a monitor which has since been unplugged doesn't come back off-screen.
+    /// Does nothing if no monitor reports a size.
+    ///
+    /// Unlike [`Self::clamp_position_to_monitors`] this runs on all platforms,
+    /// and only makes sure the window is partially visible.
+    /// Use [`Self::clamp_size_to_sane_values`] to limit the size.
+    pub fn clamp_to_monitors(
+        &mut self,
+        monitors: impl Iterator<Item = winit::monitor::MonitorHandle>,
+    ) {
+        let monitors: Vec<MonitorInfo> = monitors
+            .map(|monitor| MonitorInfo::from_monitor(&monitor))
+            .collect();
+        self.clamp_to_monitor_infos(&monitors);
+    }
+
+    fn clamp_to_monitor_infos(&mut self, monitors: &[MonitorInfo]) {
+        let Some(pos_px) = self.outer_position_pixels.or(self.inner_position_pixels) else {
+            return;
+        };
+
+        // Where the top-left corner of the window may be, for its title bar to be visible:
+        let allowed_rects = monitors
+            .iter()
+            .filter(|monitor| 0.0 < monitor.size_px.x && 0.0 < monitor.size_px.y)
+            .map(|monitor| {
+                let min_visible = egui::Vec2::splat(MIN_VISIBLE_POINTS * monitor.scale_factor)
+                    .min(monitor.size_px);
+                egui::Rect::from_min_size(monitor.position_px, monitor.size_px - min_visible)
+            });
+
+        let Some(new_pos_px) = allowed_rects
+            .map(|rect| pos_px.clamp(rect.min, rect.max))
+            .min_by(|a, b| a.distance_sq(pos_px).total_cmp(&b.distance_sq(pos_px)))
+        else {
+            return; // no monitors 🤷
+        };
+
+        let delta = new_pos_px - pos_px;
+        if let Some(pos) = &mut self.inner_position_pixels {
+            *pos += delta;
+        }
+        if let Some(pos) = &mut self.outer_position_pixels {
+            *pos += delta;
+        }
+    }
+}
+
+/// How much of a window [`WindowSettings::clamp_to_monitors`] keeps on screen, in each direction.
+const MIN_VISIBLE_POINTS: f32 = 64.0;
+
+/// How far [`WindowSettings::cascade_from`] offsets each new window from the previous one.
+const CASCADE_OFFSET_POINTS: f32 = 32.0;
+
+/// A snapshot of the configuration of a monitor.
+///
+/// Compare snapshots with [`monitors_changed_since`] to find out when the user
+/// plugged in a monitor or changed their display arrangement,
+/// e.g. to re-run [`WindowSettings::clamp_position_to_monitors`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// Human-readable name of the monitor, if known.
+    pub name: Option<String>,
+
+    /// Top-left corner of the monitor on the desktop, in physical pixels.
+    pub position_px: egui::Pos2,
+
+    /// Size of the monitor in physical pixels.
+    pub size_px: egui::Vec2,
+
+    /// The native scale factor of the monitor.
+    pub scale_factor: f32,
+}
+
+impl MonitorInfo {
+    pub fn from_monitor(monitor: &winit::monitor::MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            name: monitor.name(),
+            position_px: egui::pos2(position.x as f32, position.y as f32),
+            size_px: egui::vec2(size.width as f32, size.height as f32),
+            scale_factor: monitor.scale_factor() as f32,
+        }
+    }
+
+    /// The area covered by the monitor, in egui points.
+    ///
+    /// Both the position and the size are divided by this monitor's own scale factor,
+    /// so with mixed-DPI setups the rectangles of neighboring monitors may overlap or leave gaps.
+    pub fn rect_points(&self) -> egui::Rect {
+        egui::Rect::from_min_size(
+            self.position_px / self.scale_factor,
+            self.size_px / self.scale_factor,
+        )
+    }
+}
+
+/// Snapshot all monitors currently connected.
+pub fn available_monitors(window: &winit::window::Window) -> Vec<MonitorInfo> {
+    window
+        .available_monitors()
+        .map(|monitor| MonitorInfo::from_monitor(&monitor))
+        .collect()
+}
+
+/// Has the monitor configuration changed since the `last` snapshot taken with [`available_monitors`]?
+pub fn monitors_changed_since(window: &winit::window::Window, last: &[MonitorInfo]) -> bool {
+    monitors_differ(last, &available_monitors(window))
+}
+
+/// The order of the monitors is ignored.
+fn monitors_differ(a: &[MonitorInfo], b: &[MonitorInfo]) -> bool {
+    a.len() != b.len() || !a.iter().all(|monitor| b.contains(monitor))
 }
 
 fn clamp_pos_to_monitors<E>(
@@ -184,3 +341,153 @@ fn clamp_pos_to_monitors<E>(
     // can have.
     *position_px = position_px.clamp(monitor_position, monitor_position + window_size);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: f32, scale_factor: f32) -> MonitorInfo {
+        MonitorInfo {
+            name: None,
+            position_px: egui::pos2(x, 0.0),
+            size_px: egui::vec2(1920.0, 1080.0),
+            scale_factor,
+        }
+    }
+
+    #[test]
+    fn test_cascade() {
+        let monitor = monitor(1920.0, 2.0);
+        let offset = 2.0 * CASCADE_OFFSET_POINTS;
+
+        let first = WindowSettings::cascade_from(None, &monitor);
+        assert_eq!(
+            first.outer_position_pixels,
+            Some(egui::pos2(1920.0 + offset, offset))
+        );
+
+        let previous = WindowSettings {
+            inner_size_points: Some(egui::vec2(400.0, 300.0)),
+            ..first
+        };
+        let second = WindowSettings::cascade_from(Some(&previous), &monitor);
+        assert_eq!(
+            second.outer_position_pixels,
+            Some(egui::pos2(1920.0 + 2.0 * offset, 2.0 * offset))
+        );
+        assert_eq!(second.inner_size_points, previous.inner_size_points);
+    }
+
+    #[test]
+    fn test_cascade_resets_at_monitor_edge() {
+        let monitor = monitor(0.0, 1.0);
+        let previous = WindowSettings {
+            outer_position_pixels: Some(egui::pos2(1000.0, 800.0)),
+            inner_size_points: Some(egui::vec2(400.0, 300.0)),
+            ..Default::default()
+        };
+
+        let next = WindowSettings::cascade_from(Some(&previous), &monitor);
+        assert_eq!(
+            next.outer_position_pixels,
+            Some(egui::pos2(CASCADE_OFFSET_POINTS, CASCADE_OFFSET_POINTS))
+        );
+
+        // The previous window is on a monitor to the left of this one:
+        let right = self::monitor(1920.0, 1.0);
+        let previous = WindowSettings {
+            outer_position_pixels: Some(egui::pos2(100.0, 100.0)),
+            inner_size_points: Some(egui::vec2(400.0, 300.0)),
+            ..Default::default()
+        };
+        let next = WindowSettings::cascade_from(Some(&previous), &right);
+        assert_eq!(
+            next.outer_position_pixels,
+            Some(egui::pos2(
+                1920.0 + CASCADE_OFFSET_POINTS,
+                CASCADE_OFFSET_POINTS
+            ))
+        );
+    }
+
+    #[test]
+    fn test_monitors_differ() {
+        let left = monitor(0.0, 1.0);
+        let right = monitor(1920.0, 1.0);
+
+        let before = [left.clone(), right.clone()];
+        assert!(!monitors_differ(&before, &[left.clone(), right.clone()]));
+        assert!(!monitors_differ(&before, &[right.clone(), left.clone()]));
+
+        assert!(monitors_differ(&before, &before[..1]), "unplugged");
+        assert!(
+            monitors_differ(&before, &[left, monitor(1920.0, 2.0)]),
+            "DPI changed"
+        );
+        assert!(
+            monitors_differ(&before, &[monitor(-1920.0, 1.0), right]),
+            "rearranged"
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_monitors() {
+        let settings = |x: f32, y: f32| WindowSettings {
+            inner_position_pixels: Some(egui::pos2(x, y + 30.0)),
+            outer_position_pixels: Some(egui::pos2(x, y)),
+            inner_size_points: Some(egui::vec2(800.0, 600.0)),
+            ..Default::default()
+        };
+        let monitors = [monitor(0.0, 1.0), monitor(1920.0, 2.0)];
+
+        // Already on screen, even if partially:
+        let mut on_screen = settings(3500.0, 100.0);
+        on_screen.clamp_to_monitor_infos(&monitors);
+        assert_eq!(
+            on_screen.outer_position_pixels,
+            Some(egui::pos2(3500.0, 100.0))
+        );
+
+        // The right monitor was unplugged:
+        let mut off_screen = settings(2500.0, 100.0);
+        off_screen.clamp_to_monitor_infos(&monitors[..1]);
+        assert_eq!(
+            off_screen.outer_position_pixels,
+            Some(egui::pos2(1920.0 - MIN_VISIBLE_POINTS, 100.0))
+        );
+        assert_eq!(
+            off_screen.inner_position_pixels,
+            Some(egui::pos2(1920.0 - MIN_VISIBLE_POINTS, 130.0)),
+            "inner and outer position should move together"
+        );
+
+        // Above all monitors:
+        let mut above = settings(100.0, -500.0);
+        above.clamp_to_monitor_infos(&monitors);
+        assert_eq!(above.outer_position_pixels, Some(egui::pos2(100.0, 0.0)));
+
+        // No usable monitors:
+        let mut unchanged = settings(-5000.0, -5000.0);
+        unchanged.clamp_to_monitor_infos(&[]);
+        unchanged.clamp_to_monitor_infos(&[MonitorInfo {
+            size_px: egui::Vec2::ZERO,
+            ..monitor(0.0, 1.0)
+        }]);
+        assert_eq!(
+            unchanged.outer_position_pixels,
+            Some(egui::pos2(-5000.0, -5000.0))
+        );
+    }
+
+    #[test]
+    fn test_monitor_rect_points() {
+        assert_eq!(
+            monitor(0.0, 1.0).rect_points(),
+            egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(1920.0, 1080.0))
+        );
+        assert_eq!(
+            monitor(1920.0, 2.0).rect_points(),
+            egui::Rect::from_min_size(egui::pos2(960.0, 0.0), egui::vec2(960.0, 540.0))
+        );
+    }
+}
@@ -945,6 +945,12 @@ impl Context {
             changed: false, // must be set by the widget itself
         };
 
+        #[cfg(feature = "accesskit")]
+        if self.input(|i| i.has_accesskit_action_request(id, accesskit::Action::ScrollIntoView)) {
+            // e.g. a screen reader wants to read a widget that is scrolled out of view.
+            response.scroll_to_me(None);
+        }
+
         if !enabled || !sense.focusable || !layer_id.allow_interaction() {
             // Not interested or allowed input:
             self.memory_mut(|mem| mem.surrender_focus(id));
@@ -2851,3 +2857,28 @@ fn context_impl_send_sync() {
     fn assert_send_sync<T: Send + Sync>() {}
     assert_send_sync::<Context>();
 }
+
+#[cfg(feature = "accesskit")]
+#[test]
+fn accesskit_scroll_into_view() {
+    let ctx = Context::default();
+    let id = Id::new("widget");
+
+    let mut input = RawInput::default();
+    input
+        .events
+        .push(Event::AccessKitActionRequest(accesskit::ActionRequest {
+            action: accesskit::Action::ScrollIntoView,
+            target: id.accesskit_id(),
+            data: None,
+        }));
+
+    let _ = ctx.run(input, |ctx| {
+        crate::CentralPanel::default().show(ctx, |ui| {
+            let rect = Rect::from_min_size(pos2(0.0, 1000.0), vec2(100.0, 20.0));
+            ui.interact(rect, id, Sense::hover());
+        });
+        let scroll_target = ctx.frame_state(|fs| fs.scroll_target[1]);
+        assert_eq!(scroll_target, Some((Rangef::new(1000.0, 1020.0), None)));
+    });
+}
@@ -354,6 +354,11 @@ pub enum Event {
     /// The integration detected a "paste" event (e.g. Cmd+V).
     Paste(String),
 
+    /// The integration detected a "paste" event (e.g. Cmd+V), and the clipboard held an image.
+    ///
+    /// egui itself ignores this; it is up to the app to do something with it.
+    PasteImage(std::sync::Arc<ColorImage>),
+
     /// Text input, e.g. via keyboard.
     ///
     /// When the user presses enter/return, do not send a [`Text`](Event::Text) (just [`Key::Enter`]).
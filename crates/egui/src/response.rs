@@ -606,6 +606,7 @@ impl Response {
             x1: self.rect.max.x.into(),
             y1: self.rect.max.y.into(),
         });
+        builder.add_action(accesskit::Action::ScrollIntoView);
         if self.sense.focusable {
             builder.add_action(accesskit::Action::Focus);
         }
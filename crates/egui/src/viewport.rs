@@ -568,8 +568,22 @@ impl ViewportBuilder {
 
     /// Update this `ViewportBuilder` with a delta,
     /// returning a list of commands and a bool intdicating if the window needs to be recreated.
+    ///
+    /// Use [`Self::patch_with_reasons`] to find out _why_ the window needs to be recreated.
     #[must_use]
     pub fn patch(&mut self, new_vp_builder: ViewportBuilder) -> (Vec<ViewportCommand>, bool) {
+        let (commands, reasons) = self.patch_with_reasons(new_vp_builder);
+        (commands, reasons.any())
+    }
+
+    /// Like [`Self::patch`], but returns which fields forced a window recreation.
+    ///
+    /// This lets you skip the (expensive) recreation for changes you don't care about.
+    #[must_use]
+    pub fn patch_with_reasons(
+        &mut self,
+        new_vp_builder: ViewportBuilder,
+    ) -> (Vec<ViewportCommand>, RecreateReasons) {
         let ViewportBuilder {
             title: new_title,
             app_id: new_app_id,
@@ -682,7 +696,7 @@ impl ViewportBuilder {
         }
 
         if let Some(new_visible) = new_visible {
-            if Some(new_visible) != self.active {
+            if Some(new_visible) != self.visible {
                 self.visible = Some(new_visible);
                 commands.push(ViewportCommand::Visible(new_visible));
             }
@@ -707,63 +721,110 @@ impl ViewportBuilder {
         // The reason we don't have commands for them is that `winit` doesn't support
         // changing them without recreating the window.
 
-        let mut recreate_window = false;
+        let mut reasons = RecreateReasons::default();
 
         if new_active.is_some() && self.active != new_active {
             self.active = new_active;
-            recreate_window = true;
+            reasons.active = true;
         }
 
         if new_app_id.is_some() && self.app_id != new_app_id {
             self.app_id = new_app_id;
-            recreate_window = true;
+            reasons.app_id = true;
         }
 
         if new_close_button.is_some() && self.close_button != new_close_button {
             self.close_button = new_close_button;
-            recreate_window = true;
+            reasons.close_button = true;
         }
 
         if new_minimize_button.is_some() && self.minimize_button != new_minimize_button {
             self.minimize_button = new_minimize_button;
-            recreate_window = true;
+            reasons.minimize_button = true;
         }
 
         if new_maximize_button.is_some() && self.maximize_button != new_maximize_button {
             self.maximize_button = new_maximize_button;
-            recreate_window = true;
+            reasons.maximize_button = true;
         }
 
         if new_title_shown.is_some() && self.title_shown != new_title_shown {
             self.title_shown = new_title_shown;
-            recreate_window = true;
+            reasons.title_shown = true;
         }
 
         if new_titlebar_buttons_shown.is_some()
             && self.titlebar_buttons_shown != new_titlebar_buttons_shown
         {
             self.titlebar_buttons_shown = new_titlebar_buttons_shown;
-            recreate_window = true;
+            reasons.titlebar_buttons_shown = true;
         }
 
         if new_titlebar_shown.is_some() && self.titlebar_shown != new_titlebar_shown {
             self.titlebar_shown = new_titlebar_shown;
-            recreate_window = true;
+            reasons.titlebar_shown = true;
         }
 
         if new_fullsize_content_view.is_some()
             && self.fullsize_content_view != new_fullsize_content_view
         {
             self.fullsize_content_view = new_fullsize_content_view;
-            recreate_window = true;
+            reasons.fullsize_content_view = true;
         }
 
         if new_drag_and_drop.is_some() && self.drag_and_drop != new_drag_and_drop {
             self.drag_and_drop = new_drag_and_drop;
-            recreate_window = true;
+            reasons.drag_and_drop = true;
         }
 
-        (commands, recreate_window)
+        (commands, reasons)
+    }
+}
+
+/// The fields of a [`ViewportBuilder`] that changed in a way that requires the window to be recreated.
+///
+/// Returned by [`ViewportBuilder::patch_with_reasons`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecreateReasons {
+    pub active: bool,
+    pub app_id: bool,
+    pub close_button: bool,
+    pub minimize_button: bool,
+    pub maximize_button: bool,
+    pub title_shown: bool,
+    pub titlebar_buttons_shown: bool,
+    pub titlebar_shown: bool,
+    pub fullsize_content_view: bool,
+    pub drag_and_drop: bool,
+}
+
+impl RecreateReasons {
+    /// Does the window need to be recreated at all?
+    pub fn any(&self) -> bool {
+        let Self {
+            active,
+            app_id,
+            close_button,
+            minimize_button,
+            maximize_button,
+            title_shown,
+            titlebar_buttons_shown,
+            titlebar_shown,
+            fullsize_content_view,
+            drag_and_drop,
+        } = *self;
+
+        active
+            || app_id
+            || close_button
+            || minimize_button
+            || maximize_button
+            || title_shown
+            || titlebar_buttons_shown
+            || titlebar_shown
+            || fullsize_content_view
+            || drag_and_drop
     }
 }
 
@@ -926,6 +987,10 @@ pub enum ViewportCommand {
 
     ContentProtected(bool),
 
+    /// Move the mouse cursor to this position, in points relative to the window.
+    ///
+    /// Positions outside the window are clamped to the inside of it.
+    ///
     /// Will probably not work as expected!
     CursorPosition(Pos2),
 
@@ -1035,3 +1100,61 @@ pub struct ImmediateViewport<'a> {
     /// The user-code that shows the GUI.
     pub viewport_ui_cb: Box<dyn FnOnce(&Context) + 'a>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_reports_recreate_reasons() {
+        let mut builder = ViewportBuilder::default()
+            .with_title("Hello")
+            .with_title_shown(true)
+            .with_drag_and_drop(true);
+
+        let (commands, reasons) = builder.patch_with_reasons(
+            ViewportBuilder::default()
+                .with_title("World")
+                .with_title_shown(false)
+                .with_drag_and_drop(true),
+        );
+        assert_eq!(commands, vec![ViewportCommand::Title("World".to_owned())]);
+        assert_eq!(
+            reasons,
+            RecreateReasons {
+                title_shown: true,
+                ..Default::default()
+            }
+        );
+        assert!(reasons.any());
+
+        let (commands, recreate) = builder.patch(builder.clone());
+        assert!(commands.is_empty());
+        assert!(!recreate);
+    }
+
+    #[test]
+    fn patch_compares_visible_with_visible() {
+        let mut builder = ViewportBuilder::default()
+            .with_visible(true)
+            .with_active(true);
+
+        // Toggling visibility while `active` stays the same:
+        let (commands, recreate) = builder.patch(
+            ViewportBuilder::default()
+                .with_visible(false)
+                .with_active(true),
+        );
+        assert_eq!(commands, vec![ViewportCommand::Visible(false)]);
+        assert!(!recreate);
+
+        // Toggling `active` while visibility stays the same:
+        let (commands, recreate) = builder.patch(
+            ViewportBuilder::default()
+                .with_visible(false)
+                .with_active(false),
+        );
+        assert!(commands.is_empty());
+        assert!(recreate);
+    }
+}
@@ -36,13 +36,15 @@ impl EguiGlow {
 
         let egui_ctx = egui::Context::default();
 
-        let egui_winit = egui_winit::State::new(
+        let mut egui_winit = egui_winit::State::new(
             egui_ctx.clone(),
             ViewportId::ROOT,
             event_loop,
             native_pixels_per_point,
             Some(painter.max_texture_side()),
         );
+        // Let mice without a tilt wheel scroll horizontally:
+        egui_winit.set_horizontal_scroll_modifier(Some(egui::Modifiers::SHIFT));
 
         Self {
             egui_ctx,
@@ -80,14 +82,16 @@ impl EguiGlow {
         }
         for (_, ViewportOutput { commands, .. }) in viewport_output {
             let mut screenshot_requested = false;
-            egui_winit::process_viewport_commands(
+            for (command, err) in egui_winit::process_viewport_commands(
                 &self.egui_ctx,
                 &mut self.viewport_info,
                 commands,
                 window,
                 true,
                 &mut screenshot_requested,
-            );
+            ) {
+                log::warn!("{command:?}: {err}");
+            }
             if screenshot_requested {
                 log::warn!("Screenshot not yet supported by EguiGlow");
             }